@@ -0,0 +1,138 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Preprocess a synthetic book of 5,000 chapters the way `mdbook-course` does,
+//! and fail if that takes longer than the budget.
+//!
+//! Run with `cargo bench -p mdbook-course`.
+
+use mdbook::book::{Book, BookItem, Chapter};
+use mdbook_course::config::Config;
+use mdbook_course::course::Courses;
+use mdbook_course::frontmatter::FrontmatterCache;
+use mdbook_course::{replacements, timing_info};
+use std::process;
+use std::time::{Duration, Instant};
+
+const COURSES: usize = 2;
+const SESSIONS: usize = 10;
+const SEGMENTS: usize = 10;
+/// Slides in each segment, in addition to the segment's own chapter.
+const SLIDES: usize = 24;
+
+/// The longest that preprocessing the book may take, including extracting
+/// the course structure.
+const BUDGET: Duration = Duration::from_secs(2);
+
+fn synthetic_book() -> Book {
+    let mut book = Book::new();
+    for course in 0..COURSES {
+        for session in 0..SESSIONS {
+            for segment in 0..SEGMENTS {
+                let mut frontmatter = String::from("minutes: 5\ntarget_minutes: 60");
+                if segment == 0 {
+                    frontmatter = format!(
+                        "course: Course {course}\nsession: Session {session}\n\
+                         {frontmatter}"
+                    );
+                }
+                let dir = format!("c{course}/s{session}/g{segment}");
+                let mut chapter = Chapter::new(
+                    &format!("Segment {course}.{session}.{segment}"),
+                    format!(
+                        "---\n{frontmatter}\n---\n\n# Segment\n\n\
+                         {{{{%segment outline}}}}\n"
+                    ),
+                    format!("{dir}.md"),
+                    Vec::new(),
+                );
+                for slide in 0..SLIDES {
+                    let slide = Chapter::new(
+                        &format!("Slide {slide}"),
+                        format!(
+                            "---\nminutes: 2\n---\n\n# Slide {slide}\n\n\
+                             Some content.\n\n```rust\nfn main() {{}}\n```\n\n\
+                             <details>\n\nSpeaker notes.\n\n</details>\n"
+                        ),
+                        format!("{dir}/slide-{slide}.md"),
+                        Vec::new(),
+                    );
+                    chapter.sub_items.push(BookItem::Chapter(slide));
+                }
+                book.push_item(chapter);
+            }
+        }
+    }
+    book
+}
+
+/// Preprocess the book as the `mdbook-course` binary does for HTML output.
+fn preprocess(book: Book, config: &Config, cache: &mut FrontmatterCache) -> Book {
+    let (courses, mut book) =
+        Courses::extract_structure_cached(book, config, cache).unwrap();
+    let renderer = config.renderer("html");
+    book.for_each_mut(|item| {
+        let BookItem::Chapter(chapter) = item else {
+            return;
+        };
+        let result = match courses.find_slide(chapter) {
+            Some((course, session, segment, slide)) => {
+                timing_info::insert_timing_info(
+                    &courses,
+                    slide,
+                    &config.timing,
+                    chapter,
+                );
+                replacements::replace(
+                    &courses,
+                    Some(course),
+                    Some(session),
+                    Some(segment),
+                    Some(slide),
+                    &renderer,
+                    chapter,
+                )
+            }
+            None => replacements::replace(
+                &courses, None, None, None, None, &renderer, chapter,
+            ),
+        };
+        result.unwrap();
+    });
+    book
+}
+
+fn main() {
+    let config = Config::default();
+    let book = synthetic_book();
+    let chapters =
+        book.iter().filter(|item| matches!(item, BookItem::Chapter(_))).count();
+
+    let mut cache = FrontmatterCache::default();
+    let start = Instant::now();
+    preprocess(book.clone(), &config, &mut cache);
+    let cold = start.elapsed();
+
+    let start = Instant::now();
+    preprocess(book, &config, &mut cache);
+    let warm = start.elapsed();
+
+    println!("Preprocessed {chapters} chapters:");
+    println!("  without cached frontmatter: {cold:?}");
+    println!("  with cached frontmatter:    {warm:?}");
+    if cold > BUDGET {
+        eprintln!("Preprocessing took longer than the budget of {BUDGET:?}");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,433 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Layout of a course onto wall-clock times, for a concrete delivery.
+//!
+//! Each day is divided into one or more windows (such as 09:00-12:30 and
+//! 13:30-17:00), and each session of the course occupies the next available
+//! window. Segments and the breaks between them are laid out sequentially
+//! within the session's window.
+
+use crate::course::Course;
+use crate::csv;
+use crate::markdown::Table;
+use anyhow::Context;
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// A window of time within a day during which a session can be taught.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl FromStr for DayWindow {
+    type Err = anyhow::Error;
+
+    /// Parse a window in the form `HH:MM-HH:MM`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (start, end) =
+            s.split_once('-').with_context(|| format!("invalid window {s:?}"))?;
+        let parse = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .with_context(|| format!("invalid time {t:?} in window {s:?}"))
+        };
+        let window = DayWindow { start: parse(start)?, end: parse(end)? };
+        if window.end <= window.start {
+            anyhow::bail!("window {s:?} ends before it starts");
+        }
+        Ok(window)
+    }
+}
+
+/// Options for laying out an agenda.
+#[derive(Debug, Clone)]
+pub struct AgendaOptions {
+    /// The local date and time at which the course begins.
+    pub start: NaiveDateTime,
+    pub time_zone: Tz,
+    /// The windows in each day, in any order.
+    pub windows: Vec<DayWindow>,
+    /// Do not schedule sessions on Saturdays and Sundays.
+    pub skip_weekends: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Segment,
+    Break,
+}
+
+/// A single item in the agenda.
+#[derive(Debug)]
+pub struct Entry<'a> {
+    pub session: &'a str,
+    pub name: &'a str,
+    pub kind: EntryKind,
+    pub start: DateTime<Tz>,
+    pub end: DateTime<Tz>,
+}
+
+/// A course laid out onto wall-clock times.
+#[derive(Debug)]
+pub struct Agenda<'a> {
+    pub course: &'a Course,
+    pub time_zone: Tz,
+    pub entries: Vec<Entry<'a>>,
+    /// Problems with the layout, such as sessions overrunning their window.
+    pub warnings: Vec<String>,
+}
+
+impl<'a> Agenda<'a> {
+    /// Lay out the timed sessions of the given course.
+    pub fn new(course: &'a Course, options: &AgendaOptions) -> anyhow::Result<Self> {
+        if options.windows.is_empty() {
+            anyhow::bail!("at least one day window is required");
+        }
+        let mut windows = options.windows.clone();
+        windows.sort_by_key(|w| w.start);
+
+        let mut agenda = Agenda {
+            course,
+            time_zone: options.time_zone,
+            entries: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let local = |date: NaiveDate, time: NaiveTime| {
+            options
+                .time_zone
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .with_context(|| {
+                    format!("{date} {time} does not exist in {}", options.time_zone)
+                })
+        };
+
+        // The first session starts in the first window which has not yet ended
+        // at the start time.
+        let mut date = options.start.date();
+        let mut window_idx = windows
+            .iter()
+            .position(|w| w.end > options.start.time())
+            .unwrap_or_else(|| {
+                date = date.succ_opt().unwrap();
+                0
+            });
+        let mut first = true;
+
+        for session in course.into_iter().filter(|s| s.minutes() > 0) {
+            while options.skip_weekends
+                && matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+            {
+                date = date.succ_opt().unwrap();
+            }
+            let window = windows[window_idx];
+            // Later days, including a weekend start moved to Monday, begin at
+            // the start of the window.
+            let start_time = if first && date == options.start.date() {
+                window.start.max(options.start.time())
+            } else {
+                window.start
+            };
+            first = false;
+
+            let mut time = local(date, start_time)?;
+            for (segment, break_after) in session.timed_segments() {
+                let end = time + Duration::minutes(segment.minutes() as i64);
+                agenda.entries.push(Entry {
+                    session: &session.name,
+                    name: &segment.name,
+                    kind: EntryKind::Segment,
+                    start: time,
+                    end,
+                });
+                time = end;
+                if let Some(minutes) = break_after.filter(|m| *m > 0) {
+                    let end = time + Duration::minutes(minutes as i64);
+                    agenda.entries.push(Entry {
+                        session: &session.name,
+                        name: "Break",
+                        kind: EntryKind::Break,
+                        start: time,
+                        end,
+                    });
+                    time = end;
+                }
+            }
+
+            let window_end = local(date, window.end)?;
+            if time > window_end {
+                agenda.warnings.push(format!(
+                    "session {:?} ends at {} on {date}, {} minutes after its day \
+                     window ends at {}",
+                    session.name,
+                    time.format("%H:%M"),
+                    (time - window_end).num_minutes(),
+                    window.end.format("%H:%M"),
+                ));
+            }
+
+            window_idx += 1;
+            if window_idx == windows.len() {
+                window_idx = 0;
+                date = date.succ_opt().unwrap();
+            }
+        }
+        Ok(agenda)
+    }
+
+    /// Render the agenda as Markdown, with a table for each day.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# {} Agenda\n\nAll times are in {}.\n",
+            self.course.name, self.time_zone
+        );
+        let mut day: Option<(NaiveDate, Table<3>)> = None;
+        for entry in &self.entries {
+            let date = entry.start.date_naive();
+            if day.as_ref().is_none_or(|(d, _)| *d != date) {
+                if let Some((d, table)) = day.take() {
+                    write!(out, "\n## {}\n\n{table}", d.format("%A, %Y-%m-%d"))
+                        .unwrap();
+                }
+                day = Some((
+                    date,
+                    Table::new(["Time".into(), "Session".into(), "Segment".into()]),
+                ));
+            }
+            let (_, table) = day.as_mut().unwrap();
+            let name = match entry.kind {
+                EntryKind::Segment => entry.name.to_string(),
+                EntryKind::Break => format!("_{}_", entry.name),
+            };
+            table.add_row([
+                format!(
+                    "{} - {}",
+                    entry.start.format("%H:%M"),
+                    entry.end.format("%H:%M")
+                ),
+                entry.session.to_string(),
+                name,
+            ]);
+        }
+        if let Some((d, table)) = day {
+            write!(out, "\n## {}\n\n{table}", d.format("%A, %Y-%m-%d")).unwrap();
+        }
+        out
+    }
+
+    /// Render the agenda as CSV, with one row per segment or break.
+    pub fn to_csv(&self) -> String {
+        let mut out = csv::record([
+            "date", "start", "end", "session", "segment", "kind", "minutes",
+        ]);
+        for entry in &self.entries {
+            out.push_str(&csv::record([
+                entry.start.format("%Y-%m-%d").to_string(),
+                entry.start.format("%H:%M").to_string(),
+                entry.end.format("%H:%M").to_string(),
+                entry.session.to_string(),
+                entry.name.to_string(),
+                match entry.kind {
+                    EntryKind::Segment => "segment".into(),
+                    EntryKind::Break => "break".into(),
+                },
+                (entry.end - entry.start).num_minutes().to_string(),
+            ]));
+        }
+        out
+    }
+
+    /// Render the agenda as an iCalendar file, with one event per segment.
+    ///
+    /// The `stamp` is the time at which the calendar was generated.
+    pub fn to_ics(&self, stamp: DateTime<Utc>) -> String {
+        const FORMAT: &str = "%Y%m%dT%H%M%SZ";
+        let mut out = String::new();
+        let mut line = |s: String| out.push_str(&ics_fold(&s));
+        line("BEGIN:VCALENDAR".into());
+        line("VERSION:2.0".into());
+        line("PRODID:-//Comprehensive Rust//mdbook-course//EN".into());
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if entry.kind != EntryKind::Segment {
+                continue;
+            }
+            let start = entry.start.with_timezone(&Utc);
+            let end = entry.end.with_timezone(&Utc);
+            line("BEGIN:VEVENT".into());
+            line(format!("UID:{}-{idx}@comprehensive-rust", start.format(FORMAT)));
+            line(format!("DTSTAMP:{}", stamp.format(FORMAT)));
+            line(format!("DTSTART:{}", start.format(FORMAT)));
+            line(format!("DTEND:{}", end.format(FORMAT)));
+            line(format!(
+                "SUMMARY:{}",
+                ics_escape(&format!("{}: {}", self.course.name, entry.name))
+            ));
+            line(format!("DESCRIPTION:{}", ics_escape(entry.session)));
+            line("END:VEVENT".into());
+        }
+        line("END:VCALENDAR".into());
+        out
+    }
+}
+
+/// Escape a TEXT value for iCalendar (RFC 5545, section 3.3.11).
+fn ics_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line to at most 75 octets per line, and terminate it with
+/// CRLF (RFC 5545, section 3.1).
+fn ics_fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::course::Courses;
+    use crate::test_util::chapter;
+
+    fn courses() -> Courses {
+        crate::test_util::courses([
+            chapter(
+                "a",
+                "---\ncourse: C\nsession: Morning\nminutes: 60\n---\n",
+                Vec::new(),
+            ),
+            chapter("b", "---\nminutes: 120\n---\n", Vec::new()),
+            chapter("c", "---\nsession: Afternoon\nminutes: 30\n---\n", Vec::new()),
+            chapter("d", "---\nsession: Day 2\nminutes: 30\n---\n", Vec::new()),
+        ])
+    }
+
+    fn options() -> AgendaOptions {
+        AgendaOptions {
+            // A Friday.
+            start: NaiveDateTime::parse_from_str(
+                "2026-10-16 09:30",
+                "%Y-%m-%d %H:%M",
+            )
+            .unwrap(),
+            time_zone: "Europe/Zurich".parse().unwrap(),
+            windows: vec![
+                "13:30-17:00".parse().unwrap(),
+                "09:00-12:30".parse().unwrap(),
+            ],
+            skip_weekends: true,
+        }
+    }
+
+    #[test]
+    fn window_parse() {
+        assert!("09:00-08:00".parse::<DayWindow>().is_err());
+        assert!("9-10".parse::<DayWindow>().is_err());
+    }
+
+    #[test]
+    fn layout() {
+        let courses = courses();
+        let agenda = Agenda::new(&courses.courses[0], &options()).unwrap();
+        let times: Vec<_> = agenda
+            .entries
+            .iter()
+            .map(|e| format!("{} {}", e.start.format("%a %H:%M"), e.name))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                "Fri 09:30 a",
+                "Fri 10:30 Break",
+                "Fri 10:40 b",
+                "Fri 13:30 c",
+                "Mon 09:00 d"
+            ]
+        );
+        assert_eq!(agenda.warnings.len(), 1);
+        assert!(
+            agenda.warnings[0].contains("10 minutes after"),
+            "{:?}",
+            agenda.warnings
+        );
+    }
+
+    fn first_entry(start: &str) -> String {
+        let courses = courses();
+        let options = AgendaOptions {
+            start: NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M").unwrap(),
+            ..options()
+        };
+        let agenda = Agenda::new(&courses.courses[0], &options).unwrap();
+        agenda.entries[0].start.format("%a %H:%M").to_string()
+    }
+
+    #[test]
+    fn weekend_start() {
+        // A Saturday.
+        assert_eq!(first_entry("2026-10-17 10:00"), "Mon 09:00");
+    }
+
+    #[test]
+    fn start_after_last_window() {
+        // A Thursday.
+        assert_eq!(first_entry("2026-10-15 18:00"), "Fri 09:00");
+    }
+
+    #[test]
+    fn formats() {
+        let courses = courses();
+        let agenda = Agenda::new(&courses.courses[0], &options()).unwrap();
+        let markdown = agenda.to_markdown();
+        assert!(markdown.contains("## Monday, 2026-10-19\n"), "{markdown}");
+        assert!(
+            markdown.contains("| 10:30 - 10:40 | Morning | _Break_ |\n"),
+            "{markdown}"
+        );
+
+        let csv = agenda.to_csv();
+        assert!(csv.contains("2026-10-16,13:30,14:00,Afternoon,c,segment,30\r\n"));
+
+        let ics = agenda.to_ics(Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap());
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 4);
+        // 09:30 in Zurich is 07:30 UTC during daylight saving time.
+        assert!(ics.contains("DTSTART:20261016T073000Z\r\n"), "{ics}");
+        assert!(ics.contains("SUMMARY:C: a\r\n"));
+    }
+
+    #[test]
+    fn fold() {
+        let folded = ics_fold(&"x".repeat(100));
+        assert_eq!(folded, format!("{}\r\n {}\r\n", "x".repeat(75), "x".repeat(25)));
+    }
+}
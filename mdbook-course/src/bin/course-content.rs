@@ -12,34 +12,219 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use anyhow::Context;
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use mdbook::MDBook;
-use mdbook_course::course::Courses;
-use std::fs;
-use std::path::Path;
+use mdbook::book::BookItem;
+use mdbook_course::config::Config;
+use mdbook_course::course::{Courses, Slide};
+use mdbook_course::include::resolve_includes;
+use mdbook_course::markdown::{speaker_notes, strip_speaker_notes};
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process;
 
 fn main() {
     pretty_env_logger::init();
+    let filter = |name: &'static str, help: &'static str| {
+        Arg::new(name).long(name).help(help)
+    };
+    let flag = |name: &'static str, help: &'static str| {
+        Arg::new(name).long(name).action(ArgAction::SetTrue).help(help)
+    };
+    let matches = Command::new("course-content")
+        .about("Dump the course content, in order")
+        .arg(filter("course", "Only include the named course"))
+        .arg(filter("session", "Only include the named session"))
+        .arg(filter("segment", "Only include the named segment"))
+        .arg(flag("strip-frontmatter", "Omit the frontmatter of each chapter"))
+        .arg(
+            flag("strip-notes", "Omit speaker notes (implies --strip-frontmatter)")
+                .conflicts_with("notes-only"),
+        )
+        .arg(flag("notes-only", "Only include speaker notes"))
+        .arg(flag("resolve-includes", "Replace {{#include}} links with the content"))
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["markdown", "jsonl"])
+                .default_value("markdown")
+                .help("Output concatenated Markdown, or JSON Lines with one slide per line"),
+        )
+        .get_matches();
+
+    if let Err(e) = run(&matches) {
+        eprintln!("{e:#}");
+        process::exit(1);
+    }
+}
+
+/// What to include from each chapter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Selection {
+    /// The chapter's source, including frontmatter.
+    Source,
+    /// The chapter without its frontmatter.
+    Content,
+    /// The chapter without its frontmatter or speaker notes.
+    Body,
+    /// Only the speaker notes.
+    Notes,
+}
+
+/// One slide, as written in JSON Lines output.
+#[derive(Serialize)]
+struct SlideRecord<'a> {
+    course: &'a str,
+    session: &'a str,
+    segment: &'a str,
+    slide: &'a str,
+    source_paths: &'a [PathBuf],
+    minutes: u64,
+    text: String,
+    code_blocks: Vec<CodeBlock>,
+}
+
+#[derive(Serialize)]
+struct CodeBlock {
+    /// The info string of the code block, such as `rust,editable`.
+    info: String,
+    code: String,
+}
+
+fn run(matches: &ArgMatches) -> anyhow::Result<()> {
     let root_dir = ".";
-    let mdbook = MDBook::load(root_dir).expect("Unable to load the book");
-    let (courses, _) = Courses::extract_structure(mdbook.book)
-        .expect("Unable to extract course structure");
-
-    let src_dir = Path::new("src");
-    for course in &courses {
-        println!("# COURSE: {}", course.name);
-        for session in course {
-            println!("# SESSION: {}", session.name);
-            for segment in session {
-                println!("# SEGMENT: {}", segment.name);
+    let mdbook = MDBook::load(root_dir).context("Unable to load the book")?;
+    let config = Config::from_book_config(&mdbook.config)?;
+    let src_dir = mdbook.source_dir();
+
+    // The book as loaded contains the source of each chapter, including
+    // frontmatter, which is stripped when extracting the course structure.
+    let mut sources = HashMap::new();
+    for item in mdbook.book.iter() {
+        if let BookItem::Chapter(chapter) = item
+            && let Some(source_path) = &chapter.source_path
+        {
+            sources.insert(source_path.clone(), chapter.content.clone());
+        }
+    }
+    let (courses, book) = Courses::extract_structure(mdbook.book, &config)
+        .context("Unable to extract course structure")?;
+    let mut contents = HashMap::new();
+    for item in book.iter() {
+        if let BookItem::Chapter(chapter) = item
+            && let Some(source_path) = &chapter.source_path
+        {
+            contents.insert(source_path.clone(), chapter.content.clone());
+        }
+    }
+
+    let selection = if matches.get_flag("notes-only") {
+        Selection::Notes
+    } else if matches.get_flag("strip-notes") {
+        Selection::Body
+    } else if matches.get_flag("strip-frontmatter") {
+        Selection::Content
+    } else {
+        Selection::Source
+    };
+    let resolve = matches.get_flag("resolve-includes");
+    let chapter_text = |source_path: &Path| -> anyhow::Result<String> {
+        let map = if selection == Selection::Source { &sources } else { &contents };
+        let content = map.get(source_path).with_context(|| {
+            format!("{}: chapter not found", source_path.display())
+        })?;
+        let text = match selection {
+            Selection::Source | Selection::Content => content.clone(),
+            Selection::Body => strip_speaker_notes(content),
+            Selection::Notes => speaker_notes(content)
+                .map(|notes| content[notes].trim().to_string())
+                .unwrap_or_default(),
+        };
+        if !resolve {
+            return Ok(text);
+        }
+        let dir = src_dir.join(source_path);
+        let dir = dir.parent().unwrap_or(&src_dir);
+        resolve_includes(&text, dir).with_context(|| {
+            format!("{}: unable to resolve includes", source_path.display())
+        })
+    };
+    let slide_text = |slide: &Slide| -> anyhow::Result<String> {
+        let texts = slide
+            .source_paths
+            .iter()
+            .map(|path| chapter_text(path))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(texts.join("\n"))
+    };
+
+    let selected = |arg: &str, name: &str| {
+        matches.get_one::<String>(arg).is_none_or(|selected| selected == name)
+    };
+    let jsonl = matches.get_one::<String>("format").unwrap() == "jsonl";
+    for course in courses.into_iter().filter(|c| selected("course", &c.name)) {
+        if !jsonl {
+            println!("# COURSE: {}", course.name);
+        }
+        for session in course.into_iter().filter(|s| selected("session", &s.name)) {
+            if !jsonl {
+                println!("# SESSION: {}", session.name);
+            }
+            for segment in
+                session.into_iter().filter(|s| selected("segment", &s.name))
+            {
+                if !jsonl {
+                    println!("# SEGMENT: {}", segment.name);
+                }
                 for slide in segment {
-                    println!("# SLIDE: {}", slide.name);
-                    for path in &slide.source_paths {
-                        let content =
-                            fs::read_to_string(src_dir.join(path)).unwrap();
-                        println!("{}", content);
+                    let text = slide_text(slide)?;
+                    if !jsonl {
+                        println!("# SLIDE: {}", slide.name);
+                        println!("{text}");
+                        continue;
                     }
+                    let record = SlideRecord {
+                        course: &course.name,
+                        session: &session.name,
+                        segment: &segment.name,
+                        slide: &slide.name,
+                        source_paths: &slide.source_paths,
+                        minutes: slide.minutes(),
+                        code_blocks: code_blocks(&text),
+                        text,
+                    };
+                    println!("{}", serde_json::to_string(&record)?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collect the fenced and indented code blocks in the Markdown text.
+fn code_blocks(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<CodeBlock> = None;
+    for event in Parser::new(text) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let info = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                current = Some(CodeBlock { info, code: String::new() });
+            }
+            Event::Text(code) => {
+                if let Some(block) = &mut current {
+                    block.code.push_str(&code);
                 }
             }
+            Event::End(TagEnd::CodeBlock) => blocks.extend(current.take()),
+            _ => {}
         }
     }
+    blocks
 }
@@ -0,0 +1,68 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::{Arg, Command};
+use mdbook::MDBook;
+use mdbook_course::config::Config;
+use mdbook_course::course::Courses;
+use mdbook_course::run_sheet::RunSheet;
+use std::path::Path;
+use std::{fs, process};
+
+fn main() {
+    pretty_env_logger::init();
+    let matches = Command::new("course-run-sheet")
+        .about("Write instructor run-sheets for each session, as Markdown and HTML")
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .required(true)
+                .help("Directory in which to write the run-sheets"),
+        )
+        .arg(Arg::new("course").long("course").help("Only include the named course"))
+        .get_matches();
+
+    let root_dir = ".";
+    let mdbook = MDBook::load(root_dir).expect("Unable to load the book");
+    let config =
+        Config::from_book_config(&mdbook.config).expect("Invalid configuration");
+    let (courses, book) = Courses::extract_structure(mdbook.book, &config)
+        .expect("Unable to extract course structure");
+
+    let course_name = matches.get_one::<String>("course");
+    if let Some(course_name) = course_name
+        && courses.find_course(course_name).is_none()
+    {
+        eprintln!("Course {course_name:?} not found");
+        process::exit(1);
+    }
+
+    let out_dir = Path::new(matches.get_one::<String>("out").unwrap());
+    fs::create_dir_all(out_dir).expect("Unable to create output directory");
+    for course in &courses {
+        if course_name.is_some_and(|name| *name != course.name) {
+            continue;
+        }
+        for session in course {
+            let run_sheet =
+                RunSheet::new(course, session, &book, config.timing.rounding);
+            let stem = out_dir.join(run_sheet.file_stem());
+            fs::write(stem.with_extension("md"), run_sheet.to_markdown())
+                .expect("Unable to write Markdown run-sheet");
+            fs::write(stem.with_extension("html"), run_sheet.to_html())
+                .expect("Unable to write HTML run-sheet");
+            println!("{}", stem.display());
+        }
+    }
+}
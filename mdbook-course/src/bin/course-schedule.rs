@@ -12,82 +12,626 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use clap::Command;
+use anyhow::Context;
+use chrono::{NaiveDateTime, Utc};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use mdbook::MDBook;
-use mdbook_course::course::Courses;
-use mdbook_course::markdown::duration;
+use mdbook::book::Book;
+use mdbook_course::agenda::{Agenda, AgendaOptions, DayWindow};
+use mdbook_course::config::Config;
+use mdbook_course::course::{Course, Courses};
+use mdbook_course::export::{CoursesExport, Format};
+use mdbook_course::frontmatter::SlideKind;
+use mdbook_course::lint::{self, Severity};
+use mdbook_course::markdown::{duration, slug};
+use mdbook_course::pacing::PacingReport;
+use mdbook_course::quiz::QuizBank;
+use mdbook_course::{chart, concepts, diff};
+use serde::Serialize;
+use std::path::Path;
+use std::{fs, process};
 
 fn main() {
     pretty_env_logger::init();
     let app = Command::new("mdbook-course")
         .about("mdbook preprocessor for Comprehensive Rust")
-        .subcommand(Command::new("sessions").about("Show session summary (default)"))
-        .subcommand(Command::new("segments").about("Show segment summary"))
-        .subcommand(Command::new("pr").about("Show summary for a PR"));
+        .arg(
+            Arg::new("variant")
+                .long("variant")
+                .global(true)
+                .help("Show the named course variant instead of the courses"),
+        )
+        .subcommand(view(
+            Command::new("sessions").about("Show session summary (default)"),
+        ))
+        .subcommand(view(Command::new("segments").about("Show segment summary")))
+        .subcommand(view(Command::new("slides").about("Show slide summary")))
+        .subcommand(Command::new("pr").about("Show summary for a PR"))
+        .subcommand(
+            Command::new("diff")
+                .about("Show changes to the schedule, for a PR")
+                .arg(
+                    Arg::new("base")
+                        .long("base")
+                        .required(true)
+                        .help("Root directory of the book before the change"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export the course structure in a machine-readable format")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["json", "yaml"])
+                        .default_value("json"),
+                ),
+        )
+        .subcommand(
+            Command::new("lint")
+                .about("Check the course structure for mistakes")
+                .long_about(
+                    "Check the course structure for mistakes, exiting with a \
+                     non-zero status if any errors are found",
+                ),
+        )
+        .subcommand(
+            Command::new("pacing")
+                .about("Compare the minutes of each slide to its content")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["table", "csv"])
+                        .default_value("table"),
+                ),
+        )
+        .subcommand(
+            Command::new("chart")
+                .about("Write SVG charts of the time in each course")
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .required(true)
+                        .help("Directory in which to write the charts"),
+                )
+                .arg(
+                    Arg::new("course")
+                        .long("course")
+                        .help("Only chart the named course"),
+                ),
+        )
+        .subcommand(
+            Command::new("quiz-bank")
+                .about("Export the quizzes in each course")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["json", "markdown"])
+                        .default_value("json"),
+                )
+                .arg(
+                    Arg::new("course")
+                        .long("course")
+                        .help("Only export the named course"),
+                ),
+        )
+        .subcommand(
+            Command::new("concepts")
+                .about("Print the concept graph in Graphviz DOT format")
+                .arg(
+                    Arg::new("course")
+                        .long("course")
+                        .help("Only show the named course"),
+                ),
+        )
+        .subcommand(
+            Command::new("agenda")
+                .about("Lay out a course onto wall-clock times")
+                .arg(Arg::new("course").long("course").required(true))
+                .arg(
+                    Arg::new("start")
+                        .long("start")
+                        .required(true)
+                        .help("Local start date and time, as YYYY-MM-DDTHH:MM"),
+                )
+                .arg(
+                    Arg::new("tz")
+                        .long("tz")
+                        .default_value("UTC")
+                        .help("IANA time zone name, such as Europe/Zurich"),
+                )
+                .arg(
+                    Arg::new("day")
+                        .long("day")
+                        .action(ArgAction::Append)
+                        .required(true)
+                        .help("A window of the day for one session, as HH:MM-HH:MM"),
+                )
+                .arg(
+                    Arg::new("skip-weekends")
+                        .long("skip-weekends")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["markdown", "ics", "csv"])
+                        .default_value("markdown"),
+                ),
+        );
     let matches = app.get_matches();
 
-    let root_dir = ".";
-    let mdbook = MDBook::load(root_dir).expect("Unable to load the book");
-    let (courses, _) = Courses::extract_structure(mdbook.book)
-        .expect("Unable to extract course structure");
+    let variant = matches.get_one::<String>("variant").map(String::as_str);
+    let (config, courses, book) = load(".", variant);
+    let rounding = config.timing.rounding;
 
     match matches.subcommand() {
-        Some(("session", _)) | None => session_summary(&courses),
-        Some(("pr", _)) => pr_summary(&courses),
+        None => session_summary(
+            &courses.courses.iter().collect::<Vec<_>>(),
+            false,
+            rounding,
+        ),
+        Some(("sessions", args)) => session_summary(
+            &select_courses(&courses, args),
+            args.get_flag("json"),
+            rounding,
+        ),
+        Some(("segments", args)) => segment_summary(
+            &select_courses(&courses, args),
+            args.get_flag("json"),
+            rounding,
+        ),
+        Some(("slides", args)) => slide_summary(
+            &select_courses(&courses, args),
+            args.get_flag("json"),
+            rounding,
+        ),
+        Some(("pr", _)) => pr_summary(&courses, rounding),
+        Some(("diff", args)) => {
+            let (_, base_courses, _) =
+                load(args.get_one::<String>("base").unwrap(), variant);
+            print!("{}", diff::diff(&base_courses, &courses, 5, rounding));
+        }
+        Some(("export", args)) => {
+            let format: Format = args
+                .get_one::<String>("format")
+                .unwrap()
+                .parse()
+                .expect("Invalid format");
+            export(&courses, format);
+        }
+        Some(("agenda", args)) => agenda(&courses, args),
+        Some(("chart", args)) => {
+            let out_dir = Path::new(args.get_one::<String>("out").unwrap());
+            if let Err(e) =
+                write_charts(&select_courses(&courses, args), out_dir, rounding)
+            {
+                eprintln!("{e:#}");
+                process::exit(1);
+            }
+        }
+        Some(("quiz-bank", args)) => {
+            let banks: Vec<_> = select_courses(&courses, args)
+                .into_iter()
+                .map(QuizBank::new)
+                .collect();
+            match args.get_one::<String>("format").unwrap().as_str() {
+                "json" => print_json(&banks),
+                "markdown" => {
+                    for bank in &banks {
+                        print!("{}", bank.to_markdown());
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+        Some(("concepts", args)) => {
+            print!("{}", concepts::to_dot(select_courses(&courses, args)))
+        }
+        Some(("pacing", args)) => {
+            let report = PacingReport::new(&courses, &book);
+            match args.get_one::<String>("format").unwrap().as_str() {
+                "table" => print!("{}", report.to_table()),
+                "csv" => print!("{}", report.to_csv()),
+                _ => unreachable!(),
+            }
+        }
+        Some(("lint", _)) => {
+            let context = lint::Context::new(&courses, &book);
+            let diagnostics =
+                lint::lint(&context, &config.lint).unwrap_or_else(|e| {
+                    eprintln!("{e}");
+                    process::exit(1);
+                });
+            for diagnostic in &diagnostics {
+                println!("{diagnostic}");
+            }
+            if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+                process::exit(1);
+            }
+        }
         _ => unreachable!(),
     }
 }
 
-fn timediff(actual: u64, target: u64, slop: u64) -> String {
+/// Add the arguments common to the summary views to a subcommand.
+fn view(command: Command) -> Command {
+    command
+        .arg(Arg::new("course").long("course").help("Only show the named course"))
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the summary as JSON"),
+        )
+}
+
+/// Select the courses named by the `--course` argument, or all courses.
+fn select_courses<'a>(courses: &'a Courses, args: &ArgMatches) -> Vec<&'a Course> {
+    let Some(course_name) = args.get_one::<String>("course") else {
+        return courses.courses.iter().collect();
+    };
+    let Some(course) = courses.find_course(course_name) else {
+        eprintln!("Course {course_name:?} not found");
+        process::exit(1);
+    };
+    vec![course]
+}
+
+/// Write the session and segment charts of each course to the output
+/// directory.
+fn write_charts(
+    courses: &[&Course],
+    out_dir: &Path,
+    rounding: u64,
+) -> anyhow::Result<()> {
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("Unable to create {}", out_dir.display()))?;
+    for course in courses {
+        let stem = slug(&course.name);
+        for (suffix, svg) in [
+            ("sessions", chart::sessions_chart(course, rounding)),
+            ("segments", chart::segments_chart(course, rounding)),
+        ] {
+            let path = out_dir.join(format!("{stem}-{suffix}.svg"));
+            fs::write(&path, svg)
+                .with_context(|| format!("Unable to write {}", path.display()))?;
+            println!("{}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn print_json(value: &impl Serialize) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).expect("Unable to serialize summary")
+    );
+}
+
+/// Load the book in the given directory and extract its course structure,
+/// or only the structure of the given course variant.
+fn load(root_dir: &str, variant: Option<&str>) -> (Config, Courses, Book) {
+    let mdbook = MDBook::load(root_dir).expect("Unable to load the book");
+    let config =
+        Config::from_book_config(&mdbook.config).expect("Invalid configuration");
+    let (mut courses, book) = Courses::extract_structure(mdbook.book, &config)
+        .expect("Unable to extract course structure");
+    if let Some(variant) = variant {
+        courses = courses.into_variant(variant).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1);
+        });
+    }
+    (config, courses, book)
+}
+
+/// The percentage of instructional time spent on exercises.
+fn exercise_percent(lecture: u64, exercises: u64) -> u64 {
+    match lecture + exercises {
+        0 => 0,
+        total => exercises * 100 / total,
+    }
+}
+
+/// Describe the split of instructional time into lecture and exercises.
+fn split(lecture: u64, exercises: u64, rounding: u64) -> String {
+    format!(
+        "{} lecture, {} exercises ({}% hands-on)",
+        duration(lecture, rounding),
+        duration(exercises, rounding),
+        exercise_percent(lecture, exercises)
+    )
+}
+
+fn exercise_marker(kind: SlideKind) -> &'static str {
+    match kind {
+        SlideKind::Exercise => " (exercise)",
+        SlideKind::Lecture => "",
+    }
+}
+
+fn timediff(actual: u64, target: u64, slop: u64, rounding: u64) -> String {
     if actual > target + slop {
         format!(
             "{} (\u{23f0} *{} too long*)",
-            duration(actual),
-            duration(actual - target),
+            duration(actual, rounding),
+            duration(actual - target, rounding),
         )
     } else if actual + slop < target {
-        format!("{}: ({} short)", duration(actual), duration(target - actual),)
+        format!(
+            "{}: ({} short)",
+            duration(actual, rounding),
+            duration(target - actual, rounding),
+        )
     } else {
-        duration(actual).to_string()
+        duration(actual, rounding).to_string()
     }
 }
 
-fn session_summary(courses: &Courses) {
+#[derive(Serialize)]
+struct SessionSummary<'a> {
+    course: &'a str,
+    session: &'a str,
+    minutes: u64,
+    target_minutes: u64,
+    lecture_minutes: u64,
+    exercise_minutes: u64,
+    segments: Vec<SegmentSummary<'a>>,
+}
+
+#[derive(Serialize)]
+struct SegmentSummary<'a> {
+    name: &'a str,
+    minutes: u64,
+    lecture_minutes: u64,
+    exercise_minutes: u64,
+}
+
+fn session_summary(courses: &[&Course], json: bool, rounding: u64) {
+    if json {
+        let sessions: Vec<_> = courses
+            .iter()
+            .flat_map(|course| {
+                course.into_iter().map(|session| SessionSummary {
+                    course: &course.name,
+                    session: &session.name,
+                    minutes: session.minutes(),
+                    target_minutes: session.target_minutes(),
+                    lecture_minutes: session.lecture_minutes(),
+                    exercise_minutes: session.exercise_minutes(),
+                    segments: session
+                        .into_iter()
+                        .map(|segment| SegmentSummary {
+                            name: &segment.name,
+                            minutes: segment.minutes(),
+                            lecture_minutes: segment.lecture_minutes(),
+                            exercise_minutes: segment.exercise_minutes(),
+                        })
+                        .collect(),
+                })
+            })
+            .collect();
+        print_json(&sessions);
+        return;
+    }
     for course in courses {
         if course.target_minutes() == 0 {
-            return;
+            continue;
         }
-        for session in course {
+        for session in *course {
             println!("### {} // {}", course.name, session.name);
             println!(
                 "_{}_",
-                timediff(session.minutes(), session.target_minutes(), 15)
+                timediff(session.minutes(), session.target_minutes(), 15, rounding)
+            );
+            println!(
+                "_{}_",
+                split(
+                    session.lecture_minutes(),
+                    session.exercise_minutes(),
+                    rounding
+                )
             );
             println!();
             for segment in session {
-                println!("* {} - _{}_", segment.name, duration(segment.minutes()));
+                print!(
+                    "* {} - _{}_",
+                    segment.name,
+                    duration(segment.minutes(), rounding)
+                );
+                match segment.exercise_minutes() {
+                    0 => println!(),
+                    minutes => {
+                        println!(" (exercises: {})", duration(minutes, rounding))
+                    }
+                }
             }
             println!();
         }
     }
 }
 
-fn pr_summary(courses: &Courses) {
+#[derive(Serialize)]
+struct SegmentDetail<'a> {
+    course: &'a str,
+    session: &'a str,
+    segment: &'a str,
+    minutes: u64,
+    lecture_minutes: u64,
+    exercise_minutes: u64,
+    slides: Vec<SlideSummary<'a>>,
+}
+
+#[derive(Serialize)]
+struct SlideSummary<'a> {
+    course: &'a str,
+    session: &'a str,
+    segment: &'a str,
+    slide: &'a str,
+    source_path: Option<&'a Path>,
+    minutes: u64,
+    kind: SlideKind,
+}
+
+/// Collect the segments in the given courses, with their slides.
+fn segments<'a>(courses: &[&'a Course]) -> Vec<SegmentDetail<'a>> {
+    let mut segments = Vec::new();
+    for course in courses {
+        for session in *course {
+            for segment in session {
+                let slides = segment
+                    .into_iter()
+                    .map(|slide| SlideSummary {
+                        course: &course.name,
+                        session: &session.name,
+                        segment: &segment.name,
+                        slide: &slide.name,
+                        source_path: slide.source_path(),
+                        minutes: slide.minutes(),
+                        kind: slide.kind(),
+                    })
+                    .collect();
+                segments.push(SegmentDetail {
+                    course: &course.name,
+                    session: &session.name,
+                    segment: &segment.name,
+                    minutes: segment.minutes(),
+                    lecture_minutes: segment.lecture_minutes(),
+                    exercise_minutes: segment.exercise_minutes(),
+                    slides,
+                });
+            }
+        }
+    }
+    segments
+}
+
+fn segment_summary(courses: &[&Course], json: bool, rounding: u64) {
+    let segments = segments(courses);
+    if json {
+        print_json(&segments);
+        return;
+    }
+    for segment in &segments {
+        println!(
+            "### {} // {} // {}",
+            segment.course, segment.session, segment.segment
+        );
+        println!("_{}_", duration(segment.minutes, rounding));
+        println!(
+            "_{}_",
+            split(segment.lecture_minutes, segment.exercise_minutes, rounding)
+        );
+        println!();
+        for slide in &segment.slides {
+            println!(
+                "* {} - _{}_{}",
+                slide.slide,
+                duration(slide.minutes, rounding),
+                exercise_marker(slide.kind)
+            );
+        }
+        println!();
+    }
+}
+
+fn slide_summary(courses: &[&Course], json: bool, rounding: u64) {
+    let slides: Vec<_> =
+        segments(courses).into_iter().flat_map(|segment| segment.slides).collect();
+    if json {
+        print_json(&slides);
+        return;
+    }
+    for slide in &slides {
+        println!(
+            "* {} // {} // {} // {} - _{}_{}",
+            slide.course,
+            slide.session,
+            slide.segment,
+            slide.slide,
+            duration(slide.minutes, rounding),
+            exercise_marker(slide.kind)
+        );
+    }
+}
+
+fn pr_summary(courses: &Courses, rounding: u64) {
     println!("## Course Schedule");
     println!("With this pull request applied, the course schedule is as follows:");
     for course in courses {
         if course.target_minutes() == 0 {
-            return;
+            continue;
         }
         println!("### {}", course.name);
-        println!("_{}_", timediff(course.minutes(), course.target_minutes(), 15));
+        println!(
+            "_{}_",
+            timediff(course.minutes(), course.target_minutes(), 15, rounding)
+        );
 
         for session in course {
             println!(
-                "* {} - _{}_",
+                "* {} - _{}_ ({}% exercises)",
                 session.name,
-                timediff(session.minutes(), session.target_minutes(), 5)
+                timediff(session.minutes(), session.target_minutes(), 5, rounding),
+                exercise_percent(
+                    session.lecture_minutes(),
+                    session.exercise_minutes()
+                )
             );
         }
     }
 }
+
+fn export(courses: &Courses, format: Format) {
+    let export = CoursesExport::new(courses);
+    print!("{}", export.to_string(format).expect("Unable to serialize courses"));
+}
+
+fn agenda(courses: &Courses, args: &ArgMatches) {
+    let course_name = args.get_one::<String>("course").unwrap();
+    let Some(course) = courses.find_course(course_name) else {
+        eprintln!("Course {course_name:?} not found");
+        process::exit(1);
+    };
+    let start = args.get_one::<String>("start").unwrap();
+    let start = NaiveDateTime::parse_from_str(start, "%Y-%m-%dT%H:%M")
+        .unwrap_or_else(|e| {
+            eprintln!("Invalid start time {start:?}: {e}");
+            process::exit(1);
+        });
+    let time_zone = args.get_one::<String>("tz").unwrap();
+    let time_zone = time_zone.parse().unwrap_or_else(|e| {
+        eprintln!("Invalid time zone {time_zone:?}: {e}");
+        process::exit(1);
+    });
+    let windows = args
+        .get_many::<String>("day")
+        .unwrap()
+        .map(|day| {
+            day.parse::<DayWindow>().unwrap_or_else(|e| {
+                eprintln!("Invalid day window: {e}");
+                process::exit(1);
+            })
+        })
+        .collect();
+    let options = AgendaOptions {
+        start,
+        time_zone,
+        windows,
+        skip_weekends: args.get_flag("skip-weekends"),
+    };
+    let agenda = Agenda::new(course, &options).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+    for warning in &agenda.warnings {
+        eprintln!("warning: {warning}");
+    }
+    match args.get_one::<String>("format").unwrap().as_str() {
+        "markdown" => print!("{}", agenda.to_markdown()),
+        "ics" => print!("{}", agenda.to_ics(Utc::now())),
+        "csv" => print!("{}", agenda.to_csv()),
+        _ => unreachable!(),
+    }
+}
@@ -13,10 +13,15 @@
 // limitations under the License.
 
 use clap::{Arg, Command};
-use mdbook::book::BookItem;
+use mdbook::book::{BookItem, Chapter};
 use mdbook::preprocess::CmdPreprocessor;
+use mdbook_course::config::Config;
 use mdbook_course::course::Courses;
-use mdbook_course::{replacements, timing_info};
+use mdbook_course::frontmatter::FrontmatterCache;
+use mdbook_course::markdown::{duration, slug};
+use mdbook_course::profile::Profile;
+use mdbook_course::{profile, quiz, replacements, timing_info};
+use std::fmt::Write;
 use std::io::{stdin, stdout};
 use std::process;
 
@@ -30,7 +35,8 @@ fn main() {
     let matches = app.get_matches();
 
     if matches.subcommand_matches("supports").is_some() {
-        // Support all renderers.
+        // Support all renderers, since frontmatter must be removed for every
+        // renderer. What else is done depends on the renderer's configuration.
         process::exit(0);
     }
 
@@ -41,29 +47,145 @@ fn main() {
 }
 
 fn preprocess() -> anyhow::Result<()> {
-    let (_, book) = CmdPreprocessor::parse_input(stdin())?;
-    let (courses, mut book) = Courses::extract_structure(book)?;
+    let (ctx, book) = CmdPreprocessor::parse_input(stdin())?;
+    let config = Config::from_book_config(&ctx.config)?;
+    let profile = Profile::from_env(config.profile)?;
+    // Reuse the frontmatter parsed by previous builds, such as the rebuilds
+    // made by `mdbook serve`.
+    let cache_path = ctx
+        .root
+        .join(&ctx.config.build.build_dir)
+        .join(".mdbook-course")
+        .join("frontmatter.json");
+    let mut cache = FrontmatterCache::load(&cache_path);
+    let (courses, mut book) =
+        Courses::extract_structure_cached(book, &config, &mut cache)?;
+    if let Err(e) = cache.save(&cache_path) {
+        log::warn!("Unable to save frontmatter cache: {e:#}");
+    }
+    let renderer = config.renderer(&ctx.renderer);
+    if renderer.skip {
+        // Extracting the structure has removed the frontmatter.
+        serde_json::to_writer(stdout(), &book)?;
+        return Ok(());
+    }
+    if config.verbose {
+        eprint!("{}", timing_summary(&courses, config.timing.rounding));
+    }
+    if let Some(max_overrun) = config.timing.max_overrun {
+        check_overrun(&courses, max_overrun, config.timing.rounding)?;
+    }
 
+    // Quizzes need a browser to be interactive.
+    let interactive_quizzes = renderer.html;
+    let mut result = Ok(());
     book.for_each_mut(|chapter| {
-        if let BookItem::Chapter(chapter) = chapter {
+        if let BookItem::Chapter(chapter) = chapter
+            && result.is_ok()
+        {
             if let Some((course, session, segment, slide)) =
                 courses.find_slide(chapter)
             {
-                timing_info::insert_timing_info(slide, chapter);
-                replacements::replace(
+                if config.timing.insert && renderer.timing && profile.has_timing() {
+                    timing_info::insert_timing_info(
+                        &courses,
+                        slide,
+                        &config.timing,
+                        chapter,
+                    );
+                }
+                result = replacements::replace(
                     &courses,
                     Some(course),
                     Some(session),
                     Some(segment),
+                    Some(slide),
+                    &renderer,
                     chapter,
                 );
             } else {
                 // Outside of a course, just perform replacements.
-                replacements::replace(&courses, None, None, None, chapter);
+                result = replacements::replace(
+                    &courses, None, None, None, None, &renderer, chapter,
+                );
+            }
+            if result.is_ok() {
+                result = render_quizzes(chapter, interactive_quizzes);
             }
         }
     });
+    result?;
+    if profile == Profile::Student {
+        profile::student_edition(
+            &mut book,
+            &config.student,
+            chrono::Local::now().date_naive(),
+        );
+    }
 
     serde_json::to_writer(stdout(), &book)?;
     Ok(())
 }
+
+/// Replace the quiz blocks in a chapter.
+fn render_quizzes(chapter: &mut Chapter, interactive: bool) -> anyhow::Result<()> {
+    let Some(source_path) = &chapter.source_path else {
+        return Ok(());
+    };
+    let id = format!("quiz-{}", slug(&source_path.to_string_lossy()));
+    chapter.content = quiz::render_quizzes(&chapter.content, &id, interactive)
+        .map_err(|e| anyhow::anyhow!("{}: {e}", source_path.display()))?;
+    Ok(())
+}
+
+/// Summarize the duration of each course and session, and their targets,
+/// rounded to `rounding` minutes.
+fn timing_summary(courses: &Courses, rounding: u64) -> String {
+    let mut out = String::new();
+    for course in courses {
+        writeln!(out, "{}: {}", course.name, duration(course.minutes(), rounding))
+            .unwrap();
+        for session in course {
+            writeln!(
+                out,
+                "  {}: {} (target {})",
+                session.name,
+                duration(session.minutes(), rounding),
+                duration(session.target_minutes(), rounding)
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// Fail if any session is longer than its target by more than `max_overrun`
+/// minutes.
+fn check_overrun(
+    courses: &Courses,
+    max_overrun: u64,
+    rounding: u64,
+) -> anyhow::Result<()> {
+    let mut overruns = Vec::new();
+    for course in courses {
+        for session in course {
+            let (actual, target) = (session.minutes(), session.target_minutes());
+            if actual > target + max_overrun {
+                overruns.push(format!(
+                    "{} / {}: {} over its target of {}",
+                    course.name,
+                    session.name,
+                    duration(actual - target, rounding),
+                    duration(target, rounding)
+                ));
+            }
+        }
+    }
+    if !overruns.is_empty() {
+        anyhow::bail!(
+            "Sessions exceed their target by more than {max_overrun} minutes:\n{}",
+            overruns.join("\n")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,307 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SVG charts of where the time in a course goes.
+//!
+//! Each chart is a set of horizontal stacked bars, one per session or
+//! segment, drawn to the same scale. The SVG contains no blank lines, so it
+//! can be embedded directly in Markdown as an HTML block.
+
+use crate::course::{Course, Segment, Session};
+use crate::markdown::duration;
+use std::fmt::Write;
+
+const WIDTH: u64 = 800;
+/// Width of the labels to the left of the bars.
+const LABEL_WIDTH: u64 = 180;
+const ROW_HEIGHT: u64 = 28;
+const BAR_HEIGHT: u64 = 20;
+const TITLE_HEIGHT: u64 = 30;
+const LEGEND_HEIGHT: u64 = 30;
+/// Approximate width of a character of label text, used to decide whether a
+/// label fits in its part of a bar.
+const CHAR_WIDTH: f64 = 7.0;
+
+const COLORS: &[&str] = &[
+    "#4e79a7", "#f28e2b", "#59a14f", "#b07aa1", "#76b7b2", "#edc948", "#ff9da7",
+    "#9c755f",
+];
+const BREAK_COLOR: &str = "#d0d0d0";
+const TARGET_COLOR: &str = "#e15759";
+
+/// A part of a bar: a segment, slide or break.
+struct Part {
+    label: String,
+    minutes: u64,
+    is_break: bool,
+}
+
+/// A stacked bar, with an optional target drawn as a line across it.
+struct Bar {
+    label: String,
+    parts: Vec<Part>,
+    target: Option<u64>,
+}
+
+/// Draw a chart of the sessions in a course, with a bar for each session
+/// showing its segments and breaks against the session's target. Durations
+/// are rounded to `rounding` minutes.
+pub fn sessions_chart(course: &Course, rounding: u64) -> String {
+    let bars = course.into_iter().map(|session| Bar {
+        label: session.name.clone(),
+        parts: session_parts(session),
+        target: Some(session.target_minutes()),
+    });
+    render(&course.name, bars.collect(), rounding)
+}
+
+/// Draw a chart of the timed segments in a course, with a bar for each
+/// segment showing its slides.
+pub fn segments_chart(course: &Course, rounding: u64) -> String {
+    let bars = course.segments().filter_map(segment_bar).collect();
+    render(&course.name, bars, rounding)
+}
+
+/// Draw a chart of the timed segments in a session, with a bar for each
+/// segment showing its slides.
+pub fn session_segments_chart(session: &Session, rounding: u64) -> String {
+    let bars = session.into_iter().filter_map(segment_bar).collect();
+    render(&session.name, bars, rounding)
+}
+
+fn session_parts(session: &Session) -> Vec<Part> {
+    let mut parts = Vec::new();
+    for (segment, break_after) in session.timed_segments() {
+        parts.push(Part {
+            label: segment.name.clone(),
+            minutes: segment.minutes(),
+            is_break: false,
+        });
+        if let Some(minutes) = break_after
+            && minutes > 0
+        {
+            parts.push(Part { label: "Break".into(), minutes, is_break: true });
+        }
+    }
+    parts
+}
+
+fn segment_bar(segment: &Segment) -> Option<Bar> {
+    if segment.minutes() == 0 {
+        return None;
+    }
+    let parts = segment
+        .into_iter()
+        .filter(|slide| slide.minutes() > 0)
+        .map(|slide| Part {
+            label: slide.name.clone(),
+            minutes: slide.minutes(),
+            is_break: false,
+        })
+        .collect();
+    Some(Bar { label: segment.name.clone(), parts, target: None })
+}
+
+/// Escape text for use in SVG content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render(title: &str, bars: Vec<Bar>, rounding: u64) -> String {
+    let longest = bars
+        .iter()
+        .map(|bar| {
+            let minutes: u64 = bar.parts.iter().map(|p| p.minutes).sum();
+            minutes.max(bar.target.unwrap_or(0))
+        })
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let scale = (WIDTH - LABEL_WIDTH - 10) as f64 / longest as f64;
+    let has_breaks = bars.iter().any(|bar| bar.parts.iter().any(|p| p.is_break));
+    let has_targets = bars.iter().any(|bar| bar.target.is_some());
+    let height = TITLE_HEIGHT + ROW_HEIGHT * bars.len() as u64 + LEGEND_HEIGHT;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"course-chart\" \
+         width=\"{WIDTH}\" height=\"{height}\" viewBox=\"0 0 {WIDTH} {height}\" \
+         font-family=\"sans-serif\" font-size=\"12\">"
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<text x=\"0\" y=\"18\" font-size=\"14\" font-weight=\"bold\">{}</text>",
+        escape(title)
+    )
+    .unwrap();
+
+    for (row, bar) in bars.iter().enumerate() {
+        let y = TITLE_HEIGHT + ROW_HEIGHT * row as u64;
+        let text_y = y + BAR_HEIGHT / 2 + 4;
+        let total: u64 = bar.parts.iter().map(|p| p.minutes).sum();
+        writeln!(
+            svg,
+            "<text x=\"0\" y=\"{text_y}\"><title>{} ({})</title>{}</text>",
+            escape(&bar.label),
+            duration(total, rounding),
+            escape(&truncate(&bar.label, LABEL_WIDTH as f64 - 10.0))
+        )
+        .unwrap();
+
+        let mut x = LABEL_WIDTH as f64;
+        let mut color = 0;
+        for part in &bar.parts {
+            let width = part.minutes as f64 * scale;
+            let fill = if part.is_break {
+                BREAK_COLOR
+            } else {
+                color += 1;
+                COLORS[(color - 1) % COLORS.len()]
+            };
+            writeln!(
+                svg,
+                "<rect x=\"{x:.1}\" y=\"{y}\" width=\"{width:.1}\" \
+                 height=\"{BAR_HEIGHT}\" fill=\"{fill}\" stroke=\"white\">\
+                 <title>{} ({})</title></rect>",
+                escape(&part.label),
+                duration(part.minutes, rounding)
+            )
+            .unwrap();
+            if !part.is_break {
+                let label = truncate(&part.label, width - 6.0);
+                if !label.is_empty() {
+                    writeln!(
+                        svg,
+                        "<text x=\"{:.1}\" y=\"{text_y}\" fill=\"white\" \
+                         pointer-events=\"none\">{}</text>",
+                        x + 3.0,
+                        escape(&label)
+                    )
+                    .unwrap();
+                }
+            }
+            x += width;
+        }
+
+        if let Some(target) = bar.target {
+            let target_x = LABEL_WIDTH as f64 + target as f64 * scale;
+            writeln!(
+                svg,
+                "<line x1=\"{target_x:.1}\" y1=\"{}\" x2=\"{target_x:.1}\" y2=\"{}\" \
+                 stroke=\"{TARGET_COLOR}\" stroke-width=\"2\" \
+                 stroke-dasharray=\"4 2\"><title>{}</title></line>",
+                y.saturating_sub(2),
+                y + BAR_HEIGHT + 2,
+                duration(target, rounding)
+            )
+            .unwrap();
+        }
+    }
+
+    let legend_y = height - LEGEND_HEIGHT / 2;
+    let mut legend_x = LABEL_WIDTH;
+    if has_breaks {
+        writeln!(
+            svg,
+            "<rect x=\"{legend_x}\" y=\"{}\" width=\"12\" height=\"12\" \
+             fill=\"{BREAK_COLOR}\"/><text x=\"{}\" y=\"{}\">Break</text>",
+            legend_y - 6,
+            legend_x + 16,
+            legend_y + 4,
+        )
+        .unwrap();
+        legend_x += 100;
+    }
+    if has_targets {
+        writeln!(
+            svg,
+            "<line x1=\"{legend_x}\" y1=\"{legend_y}\" x2=\"{}\" y2=\"{legend_y}\" \
+             stroke=\"{TARGET_COLOR}\" stroke-width=\"2\" \
+             stroke-dasharray=\"4 2\"/><text x=\"{}\" y=\"{}\">target_minutes</text>",
+            legend_x + 12,
+            legend_x + 16,
+            legend_y + 4
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Shorten a label to fit in the given width, or return an empty string if
+/// not even an abbreviated label fits.
+fn truncate(label: &str, width: f64) -> String {
+    let max_chars = (width / CHAR_WIDTH).floor().max(0.0) as usize;
+    let chars = label.chars().count();
+    if chars <= max_chars {
+        label.to_string()
+    } else if max_chars >= 4 {
+        let mut short: String = label.chars().take(max_chars - 1).collect();
+        short.push('\u{2026}');
+        short
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::course::Courses;
+    use crate::test_util::chapter;
+
+    fn courses() -> Courses {
+        crate::test_util::courses([
+            chapter(
+                "A & B",
+                "---\ncourse: C\nsession: S\nminutes: 20\ntarget_minutes: 60\n---\n",
+                vec![chapter("a1", "---\nminutes: 10\n---\n", Vec::new())],
+            ),
+            chapter("c", "---\nminutes: 20\n---\n", Vec::new()),
+        ])
+    }
+
+    #[test]
+    fn sessions() {
+        let courses = courses();
+        let svg = sessions_chart(&courses.courses[0], 5);
+        assert!(svg.starts_with("<svg "));
+        assert!(!svg.contains("\n\n"));
+        assert!(svg.contains("<title>A &amp; B (30 minutes)</title>"));
+        assert!(svg.contains("<title>Break (10 minutes)</title>"));
+        // The target is drawn at 60 of the 60 minutes in the session.
+        assert!(svg.contains("<line x1=\"790.0\""));
+    }
+
+    #[test]
+    fn segments() {
+        let courses = courses();
+        let svg = segments_chart(&courses.courses[0], 5);
+        assert_eq!(svg.matches("<rect ").count(), 3);
+        assert!(svg.contains("<title>a1 (10 minutes)</title>"));
+        assert!(!svg.contains("Break"));
+    }
+
+    #[test]
+    fn truncated_labels() {
+        assert_eq!(truncate("Hello", 100.0), "Hello");
+        assert_eq!(truncate("Hello, World", 42.0), "Hello\u{2026}");
+        assert_eq!(truncate("Hello", 14.0), "");
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Concepts introduced and required by slides, given by the `provides` and
+//! `requires` fields of their frontmatter.
+//!
+//! Within a course, each concept a slide requires should be provided by an
+//! earlier slide (or the slide itself).
+
+use crate::course::{Course, Slide};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// A problem with the order of concepts in a course.
+#[derive(Debug)]
+pub enum Problem<'a> {
+    /// The concept is only provided by a later slide.
+    ProvidedLater { slide: &'a Slide, concept: &'a str, provider: &'a Slide },
+    /// No slide in the course provides the concept.
+    NotProvided { slide: &'a Slide, concept: &'a str },
+}
+
+/// Iterate over the slides of a course, in order.
+fn slides(course: &Course) -> impl Iterator<Item = &Slide> {
+    course.segments().flat_map(|segment| segment.into_iter())
+}
+
+/// Check that every concept required by a slide in the course is provided by
+/// an earlier slide.
+pub fn check(course: &Course) -> Vec<Problem<'_>> {
+    // The index and slide which first provides each concept.
+    let mut providers: BTreeMap<&str, (usize, &Slide)> = BTreeMap::new();
+    for (index, slide) in slides(course).enumerate() {
+        for concept in slide.provides() {
+            providers.entry(concept).or_insert((index, slide));
+        }
+    }
+
+    let mut problems = Vec::new();
+    for (index, slide) in slides(course).enumerate() {
+        for concept in slide.requires() {
+            match providers.get(concept.as_str()) {
+                Some((provider_index, _)) if *provider_index <= index => {}
+                Some((_, provider)) => problems.push(Problem::ProvidedLater {
+                    slide,
+                    concept,
+                    provider,
+                }),
+                None => problems.push(Problem::NotProvided { slide, concept }),
+            }
+        }
+    }
+    problems
+}
+
+/// Quote a string for use as a Graphviz ID.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render the dependency graph of the given courses in Graphviz DOT format.
+///
+/// Each course is a cluster of the slides which provide or require concepts,
+/// with an edge, labeled with the concept, from the first slide providing a
+/// concept to each slide requiring it. Concepts which no slide provides are
+/// shown as dashed nodes.
+pub fn to_dot<'a>(courses: impl IntoIterator<Item = &'a Course>) -> String {
+    let mut out = String::from("digraph concepts {\n  rankdir=LR;\n");
+    for (index, course) in courses.into_iter().enumerate() {
+        let node = |id: &str| quote(&format!("{}/{id}", course.name));
+        let slide_node = |slide: &Slide| {
+            node(
+                &slide
+                    .source_path()
+                    .map_or_else(Default::default, |p| p.to_string_lossy()),
+            )
+        };
+        writeln!(out, "  subgraph cluster_{index} {{").unwrap();
+        writeln!(out, "    label={};", quote(&course.name)).unwrap();
+
+        let mut providers: BTreeMap<&str, &Slide> = BTreeMap::new();
+        for slide in slides(course) {
+            if !slide.requires().is_empty() || !slide.provides().is_empty() {
+                writeln!(
+                    out,
+                    "    {} [label={}];",
+                    slide_node(slide),
+                    quote(&slide.name)
+                )
+                .unwrap();
+            }
+            for concept in slide.provides() {
+                providers.entry(concept).or_insert(slide);
+            }
+        }
+        for slide in slides(course) {
+            for concept in slide.requires() {
+                let from = match providers.get(concept.as_str()) {
+                    Some(provider) => slide_node(provider),
+                    None => {
+                        let from = node(&format!("concept:{concept}"));
+                        writeln!(
+                            out,
+                            "    {from} [label={}, shape=box, style=dashed];",
+                            quote(concept)
+                        )
+                        .unwrap();
+                        from
+                    }
+                };
+                writeln!(
+                    out,
+                    "    {from} -> {} [label={}];",
+                    slide_node(slide),
+                    quote(concept)
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("  }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::course::Courses;
+    use crate::test_util::chapter;
+
+    fn courses() -> Courses {
+        crate::test_util::courses([
+            chapter(
+                "a",
+                "---\ncourse: C\nsession: S\nprovides: [iterators]\n---\n",
+                Vec::new(),
+            ),
+            chapter(
+                "b",
+                "---\nrequires: [iterators, boxes]\nprovides: [\"closures\"]\n---\n",
+                Vec::new(),
+            ),
+            chapter(
+                "c",
+                "---\nrequires: [traits]\nprovides: [boxes]\n---\n",
+                Vec::new(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn check_order() {
+        let courses = courses();
+        let problems: Vec<String> = check(&courses.courses[0])
+            .iter()
+            .map(|problem| match problem {
+                Problem::ProvidedLater { slide, concept, provider } => {
+                    format!("{} {concept} later {}", slide.name, provider.name)
+                }
+                Problem::NotProvided { slide, concept } => {
+                    format!("{} {concept} missing", slide.name)
+                }
+            })
+            .collect();
+        assert_eq!(problems, vec!["b boxes later c", "c traits missing"]);
+    }
+
+    #[test]
+    fn dot() {
+        assert_eq!(
+            to_dot(&courses()),
+            "digraph concepts {\n  rankdir=LR;\n  subgraph cluster_0 {\n    \
+             label=\"C\";\n    \
+             \"C/a.md\" [label=\"a\"];\n    \
+             \"C/b.md\" [label=\"b\"];\n    \
+             \"C/c.md\" [label=\"c\"];\n    \
+             \"C/a.md\" -> \"C/b.md\" [label=\"iterators\"];\n    \
+             \"C/c.md\" -> \"C/b.md\" [label=\"boxes\"];\n    \
+             \"C/concept:traits\" [label=\"traits\", shape=box, style=dashed];\n    \
+             \"C/concept:traits\" -> \"C/c.md\" [label=\"traits\"];\n  }\n}\n"
+        );
+    }
+}
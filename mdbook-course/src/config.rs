@@ -0,0 +1,286 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Configuration for mdbook-course, read from the `[preprocessor.course]`
+//! section of `book.toml`.
+
+use crate::markdown::TableStyle;
+use crate::profile::Profile;
+use anyhow::Context;
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    /// Report the timing of each course while building the book.
+    pub verbose: bool,
+    pub breaks: BreakConfig,
+    pub lint: LintConfig,
+    pub timing: TimingConfig,
+    /// Variants of courses, keyed by the name of the variant.
+    pub variants: BTreeMap<String, VariantConfig>,
+    /// Options for each renderer, keyed by renderer name, overriding the
+    /// defaults given by [`RendererConfig::resolve`]. This is not called
+    /// `renderers`, which mdBook uses for the renderers a preprocessor runs
+    /// for.
+    pub renderer_options: BTreeMap<String, RendererConfig>,
+    /// The edition of the book to build. The `MDBOOK_COURSE_PROFILE`
+    /// environment variable takes precedence.
+    pub profile: Profile,
+    pub student: StudentConfig,
+}
+
+/// The durations of breaks, in minutes.
+///
+/// The break after a particular segment can also be set with `break_after` in
+/// the segment's frontmatter.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BreakConfig {
+    /// Break between timed segments in a session.
+    pub between_segments: u64,
+    /// Break after a segment ending with an exercise, if different from
+    /// `between_segments`.
+    pub after_exercise: Option<u64>,
+    /// Break between sessions in a course, such as a lunch break.
+    pub between_sessions: u64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct LintConfig {
+    /// Source paths at which each lint rule is allowed, keyed by rule name. A
+    /// path of `*` allows the rule everywhere.
+    pub allow: BTreeMap<String, Vec<PathBuf>>,
+}
+
+/// How timing information is added to the book.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TimingConfig {
+    /// Add timing information to the speaker notes of each slide.
+    pub insert: bool,
+    /// Add a speaker notes block to timed slides which do not have one.
+    pub create_notes: bool,
+    /// Granularity, in minutes, to which durations are rounded.
+    pub rounding: u64,
+    /// Fail the build if a session is longer than its target by more than
+    /// this many minutes.
+    pub max_overrun: Option<u64>,
+}
+
+/// A variant of a course, such as a compressed schedule, derived from the
+/// course's structure in `SUMMARY.md`.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct VariantConfig {
+    /// The name of the course this is a variant of.
+    pub course: String,
+    /// Names of segments to leave out of the variant.
+    pub drop_segments: Vec<String>,
+    /// Keep slides with `optional: true` in their frontmatter, which are
+    /// otherwise left out of the variant.
+    pub include_optional: bool,
+    /// New `target_minutes` for sessions, keyed by session name.
+    pub target_minutes: BTreeMap<String, u64>,
+}
+
+/// How the book is preprocessed for a renderer. Options which are not given
+/// take the renderer's defaults.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RendererConfig {
+    /// Only remove frontmatter, leaving directives unexpanded.
+    pub skip: Option<bool>,
+    /// Add timing information to speaker notes.
+    pub timing: Option<bool>,
+    /// Link outlines to the segments and slides they mention.
+    pub links: Option<bool>,
+    /// The style of generated tables.
+    pub tables: Option<TableStyle>,
+}
+
+/// Options for the student edition of the book.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct StudentConfig {
+    /// The date from which solutions are included. Before this date, solution
+    /// chapters only say when the solution will be available. Without a date,
+    /// solution chapters are left out.
+    #[serde(deserialize_with = "deserialize_date")]
+    pub solutions_from: Option<NaiveDate>,
+}
+
+/// Deserialize an optional date given as a `YYYY-MM-DD` string.
+fn deserialize_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|date| date.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// The options for a renderer, after applying defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RendererOptions {
+    /// Whether the output is mdBook's HTML, which shows charts and interactive
+    /// quizzes. This is also the case for `xgettext`, which extracts the
+    /// messages of the HTML output for translation.
+    pub html: bool,
+    pub skip: bool,
+    pub timing: bool,
+    pub links: bool,
+    pub tables: TableStyle,
+    /// Granularity, in minutes, to which durations are rounded, from
+    /// [`TimingConfig::rounding`].
+    pub rounding: u64,
+}
+
+impl RendererConfig {
+    /// Apply the defaults for the named renderer to these options, rounding
+    /// durations to `rounding` minutes.
+    ///
+    /// Only HTML output has speaker notes and can follow links between
+    /// chapters. Pandoc gets tables it can lay out, and the `exerciser`, which
+    /// only extracts exercises from the book, gets it without generated text.
+    /// `xgettext` extracts the messages of the HTML output, so it gets the same
+    /// text as `html`; otherwise the `.po` files would not have the messages
+    /// which are translated in it.
+    pub fn resolve(&self, renderer: &str, rounding: u64) -> RendererOptions {
+        let skip = renderer == "exerciser";
+        let html = matches!(renderer, "html" | "xgettext");
+        let tables =
+            if renderer == "pandoc" { TableStyle::Pandoc } else { TableStyle::Gfm };
+        RendererOptions {
+            html,
+            skip: self.skip.unwrap_or(skip),
+            timing: self.timing.unwrap_or(html),
+            links: self.links.unwrap_or(html),
+            tables: self.tables.unwrap_or(tables),
+            rounding,
+        }
+    }
+}
+
+impl Default for BreakConfig {
+    fn default() -> Self {
+        BreakConfig {
+            between_segments: 10,
+            after_exercise: None,
+            between_sessions: 0,
+        }
+    }
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        TimingConfig {
+            insert: true,
+            create_notes: false,
+            rounding: 5,
+            max_overrun: None,
+        }
+    }
+}
+
+impl Config {
+    /// Read the configuration from the book's configuration. A missing
+    /// `[preprocessor.course]` section results in the default configuration.
+    pub fn from_book_config(config: &mdbook::Config) -> anyhow::Result<Self> {
+        Ok(config
+            .get_deserialized_opt("preprocessor.course")
+            .context("Invalid [preprocessor.course] configuration")?
+            .unwrap_or_default())
+    }
+}
+
+impl Config {
+    /// Return the options for the named renderer.
+    pub fn renderer(&self, renderer: &str) -> RendererOptions {
+        self.renderer_options
+            .get(renderer)
+            .cloned()
+            .unwrap_or_default()
+            .resolve(renderer, self.timing.rounding)
+    }
+}
+
+impl LintConfig {
+    /// Determine whether the given rule is allowed at the given path.
+    pub fn is_allowed(&self, rule: &str, source_path: &std::path::Path) -> bool {
+        self.allow.get(rule).is_some_and(|paths| {
+            paths.iter().any(|p| p.as_os_str() == "*" || p == source_path)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn renderer_defaults() {
+        let mut config = Config::default();
+        let html = config.renderer("html");
+        assert!(html.timing && html.links && !html.skip);
+        assert_eq!(config.renderer("pandoc").tables, TableStyle::Pandoc);
+        assert!(config.renderer("exerciser").skip);
+        assert_eq!(config.renderer("xgettext"), html);
+        assert!(!config.renderer("markdown").timing);
+
+        config.renderer_options.insert(
+            "pandoc".into(),
+            RendererConfig { tables: Some(TableStyle::Gfm), ..Default::default() },
+        );
+        let pandoc = config.renderer("pandoc");
+        assert_eq!((pandoc.tables, pandoc.links), (TableStyle::Gfm, false));
+    }
+
+    #[test]
+    fn renderer_options() {
+        // `renderers` is mdBook's own list of renderers to run for.
+        let book_config: mdbook::Config = "[preprocessor.course]\n\
+                                           renderers = [\"html\", \"pandoc\"]\n\
+                                           [preprocessor.course.renderer-options.pandoc]\n\
+                                           tables = \"gfm\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_config(&book_config).unwrap();
+        assert_eq!(config.renderer("pandoc").tables, TableStyle::Gfm);
+    }
+
+    #[test]
+    fn student() {
+        let book_config: mdbook::Config = "[preprocessor.course]\n\
+                                           profile = \"student\"\n\
+                                           student.solutions-from = \"2026-11-03\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_config(&book_config).unwrap();
+        assert_eq!(config.profile, Profile::Student);
+        assert_eq!(
+            config.student.solutions_from,
+            NaiveDate::from_ymd_opt(2026, 11, 3)
+        );
+
+        let invalid: mdbook::Config =
+            "[preprocessor.course.student]\nsolutions-from = \"soon\"\n"
+                .parse()
+                .unwrap();
+        assert!(Config::from_book_config(&invalid).is_err());
+    }
+}
@@ -34,15 +34,19 @@
 //! corresponds 1-to-1 with a segment (as long as it is a chapter), and that
 //! item becomes the first slide in that segment. Any other sub-items of the
 //! top-level item are treated as further slides in the same segment.
+//!
+//! Variants of a course, declared in `book.toml`, are derived from the course
+//! after its structure is extracted, by leaving out segments and optional
+//! slides and changing the target duration of sessions.
 
-use crate::frontmatter::{Frontmatter, split_frontmatter};
-use crate::markdown::{Table, duration};
-use mdbook::book::{Book, BookItem, Chapter};
+use crate::config::{BreakConfig, Config, RendererOptions, VariantConfig};
+use crate::frontmatter::{Frontmatter, FrontmatterCache, SlideKind};
+use crate::markdown::{Links, Table, duration};
+use crate::quiz::{Quiz, parse_quizzes};
+use mdbook::book::{Book, BookItem, Chapter, SectionNumber};
+use std::collections::HashMap;
 use std::fmt::Write;
-use std::path::PathBuf;
-
-/// Duration, in minutes, of breaks between segments in the course.
-const BREAK_DURATION: u64 = 10;
+use std::path::{Path, PathBuf};
 
 /// Courses is simply a collection of Courses.
 ///
@@ -51,16 +55,32 @@ const BREAK_DURATION: u64 = 10;
 #[derive(Default, Debug)]
 pub struct Courses {
     pub courses: Vec<Course>,
+    /// Variants of the courses, named by the variant.
+    variants: Vec<Course>,
+    /// The positions in `courses` of the slides containing each source path,
+    /// in book order.
+    index: HashMap<PathBuf, Vec<SlidePosition>>,
+}
+
+/// The indices of a course, session, segment and slide within [`Courses`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlidePosition {
+    course: usize,
+    session: usize,
+    segment: usize,
+    slide: usize,
 }
 
 /// A Course is the level of content at which students enroll.
 ///
 /// Courses are identified by the `course` property in a session's frontmatter.
 /// All sessions with the same value for `course` are grouped into a Course.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Course {
     pub name: String,
     pub sessions: Vec<Session>,
+    /// Duration, in minutes, of breaks between sessions.
+    break_between_sessions: u64,
 }
 
 /// A Session is a block of instructional time, containing segments. Typically a
@@ -68,7 +88,7 @@ pub struct Course {
 ///
 /// A session is identified by the `session` property in the session's
 /// frontmatter. There can be only one session with a given name in a course.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Session {
     pub name: String,
     pub segments: Vec<Segment>,
@@ -78,42 +98,125 @@ pub struct Session {
 /// A Segment is a collection of slides with a related theme.
 ///
 /// A segment is identified as a top-level chapter within a session.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Segment {
     pub name: String,
     pub slides: Vec<Slide>,
+    /// The `target_minutes` given in this segment's frontmatter, if any.
+    target_minutes: Option<u64>,
+    /// Duration, in minutes, of the break after this segment, if it is
+    /// followed by another timed segment.
+    break_after: u64,
 }
 
 /// A Slide presents a single topic. It may contain multiple mdBook chapters.
 ///
 /// A slide is identified as an sub-chapter of a segment. Any sub-items of
 /// that chapter are also included in the slide.
-#[derive(Default, Debug)]
+///
+/// The same chapter may appear in several courses, in which case each
+/// appearance is a distinct slide, distinguished by the chapter's section
+/// number, and may have course-specific `minutes`.
+#[derive(Default, Debug, Clone)]
 pub struct Slide {
     pub name: String,
     /// Minutes this slide should take to teach.
     pub minutes: u64,
     /// Source paths (`.md` files) in this slide.
     pub source_paths: Vec<PathBuf>,
+    /// The chapters in this slide, in the same order as `source_paths`.
+    chapters: Vec<SlideChapter>,
+    /// Concepts required by the chapters in this slide.
+    requires: Vec<String>,
+    /// Concepts introduced by the chapters in this slide.
+    provides: Vec<String>,
+    /// Whether this slide is left out of course variants.
+    optional: bool,
+    /// Whether this slide is lecture material or an exercise.
+    kind: SlideKind,
+    /// Quizzes in the chapters of this slide.
+    quizzes: Vec<Quiz>,
+}
+
+/// A chapter within a slide.
+#[derive(Debug, Clone)]
+struct SlideChapter {
+    source_path: PathBuf,
+    number: Option<SectionNumber>,
+    /// Minutes given in the chapter's frontmatter for this course.
+    minutes: u64,
 }
 
 impl Courses {
     /// Extract the course structure from the book. As a side-effect, the
     /// frontmatter is stripped from each slide.
-    pub fn extract_structure(mut book: Book) -> anyhow::Result<(Self, Book)> {
+    ///
+    /// All frontmatter in the book is validated before the structure is
+    /// extracted, and any errors are reported together.
+    pub fn extract_structure(
+        book: Book,
+        config: &Config,
+    ) -> anyhow::Result<(Self, Book)> {
+        Self::extract_structure_cached(
+            book,
+            config,
+            &mut FrontmatterCache::default(),
+        )
+    }
+
+    /// Extract the course structure from the book, like
+    /// [`Courses::extract_structure`], reusing the parsed frontmatter in the
+    /// cache and adding any newly parsed frontmatter to it.
+    pub fn extract_structure_cached(
+        mut book: Book,
+        config: &Config,
+        cache: &mut FrontmatterCache,
+    ) -> anyhow::Result<(Self, Book)> {
+        cache.check_book(&book)?;
+
         let mut courses = Courses::default();
         let mut current_course_name = None;
         let mut current_session_name = None;
+        // The last part title or separator, if it came after a segment of the
+        // current course.
+        let mut boundary = None;
 
         for item in &mut book.sections {
-            // We only want to process chapters, omitting part titles and separators.
-            let BookItem::Chapter(chapter) = item else {
-                continue;
+            // Part titles and separators may only divide sessions or courses,
+            // which is checked at the following chapter.
+            let chapter = match item {
+                BookItem::Chapter(chapter) => chapter,
+                BookItem::PartTitle(title) => {
+                    if current_course_name.is_some() {
+                        boundary = Some(format!("part title {title:?}"));
+                    }
+                    continue;
+                }
+                BookItem::Separator => {
+                    if current_course_name.is_some() {
+                        boundary = Some("separator".to_string());
+                    }
+                    continue;
+                }
             };
 
-            let (frontmatter, content) = split_frontmatter(chapter)?;
+            let (frontmatter, content) = cache.split(chapter)?;
             chapter.content = content;
 
+            if let Some(boundary) = boundary.take()
+                && frontmatter.course.is_none()
+                && frontmatter.session.is_none()
+            {
+                anyhow::bail!(
+                    "{:?}: {boundary} inside session {:?} of course {:?}; part titles \
+                     and separators may only come before a chapter beginning a \
+                     session or course",
+                    chapter.path,
+                    current_session_name.unwrap_or_default(),
+                    current_course_name.unwrap_or_default(),
+                );
+            }
+
             // If 'course' is given, use that course (if not 'none') and reset the
             // session.
             if let Some(course_name) = &frontmatter.course {
@@ -142,27 +245,142 @@ impl Courses {
             if let (Some(course_name), Some(session_name)) =
                 (&current_course_name, &current_session_name)
             {
-                let course = courses.course_mut(course_name);
-                let session = course.session_mut(session_name);
+                let in_chapter =
+                    |e: anyhow::Error| anyhow::anyhow!("{:?}: {e}", chapter.path);
+                let course = courses
+                    .course_mut(course_name, &config.breaks)
+                    .map_err(in_chapter)?;
+                let session =
+                    course.session_mut(session_name).map_err(in_chapter)?;
                 session.target_minutes += frontmatter.target_minutes.unwrap_or(0);
-                session.add_segment(frontmatter, chapter)?;
+                session.add_segment(
+                    frontmatter,
+                    chapter,
+                    course_name,
+                    &config.breaks,
+                    cache,
+                )?;
             }
         }
+
+        courses.variants = config
+            .variants
+            .iter()
+            .map(|(name, variant)| courses.derive_variant(name, variant))
+            .collect::<anyhow::Result<_>>()?;
+        courses.build_index();
         Ok((courses, book))
     }
 
-    /// Get a reference to a course, adding a new one if none by this name
-    /// exists.
-    fn course_mut(&mut self, name: impl AsRef<str>) -> &mut Course {
+    /// Index the slides in `courses` by their source paths.
+    fn build_index(&mut self) {
+        self.index.clear();
+        for (course_idx, course) in self.courses.iter().enumerate() {
+            for (session_idx, session) in course.sessions.iter().enumerate() {
+                for (segment_idx, segment) in session.segments.iter().enumerate() {
+                    for (slide_idx, slide) in segment.slides.iter().enumerate() {
+                        let position = SlidePosition {
+                            course: course_idx,
+                            session: session_idx,
+                            segment: segment_idx,
+                            slide: slide_idx,
+                        };
+                        for source_path in &slide.source_paths {
+                            let positions =
+                                self.index.entry(source_path.clone()).or_default();
+                            // A slide may contain the same chapter more than once.
+                            if !positions.contains(&position) {
+                                positions.push(position);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Derive a variant of a course, named `name`.
+    fn derive_variant(
+        &self,
+        name: &str,
+        variant: &VariantConfig,
+    ) -> anyhow::Result<Course> {
+        if self.find_course(name).is_some() {
+            anyhow::bail!("variant {name:?} has the same name as a course");
+        }
+        let base = self.find_course(&variant.course).ok_or_else(|| {
+            anyhow::anyhow!(
+                "variant {name:?}: course {:?} not found",
+                variant.course
+            )
+        })?;
+        for segment_name in &variant.drop_segments {
+            if !base.segments().any(|s| &s.name == segment_name) {
+                anyhow::bail!(
+                    "variant {name:?}: segment {segment_name:?} not found in course {:?}",
+                    base.name
+                );
+            }
+        }
+        for session_name in variant.target_minutes.keys() {
+            if !base.into_iter().any(|s| &s.name == session_name) {
+                anyhow::bail!(
+                    "variant {name:?}: session {session_name:?} not found in course {:?}",
+                    base.name
+                );
+            }
+        }
+
+        let mut course = Course {
+            name: name.into(),
+            sessions: Vec::new(),
+            break_between_sessions: base.break_between_sessions,
+        };
+        for session in base {
+            let mut session = session.clone();
+            session.segments.retain(|s| !variant.drop_segments.contains(&s.name));
+            if !variant.include_optional {
+                for segment in &mut session.segments {
+                    segment.slides.retain(|slide| !slide.optional);
+                }
+                session.segments.retain(|s| !s.slides.is_empty());
+            }
+            if let Some(target_minutes) = variant.target_minutes.get(&session.name) {
+                session.target_minutes = *target_minutes;
+            }
+            if !session.segments.is_empty() {
+                course.sessions.push(session);
+            }
+        }
+        Ok(course)
+    }
+
+    /// Get a reference to a course, adding a new one with the given breaks if
+    /// none by this name exists.
+    ///
+    /// The segments of a course must be contiguous, so it is an error to
+    /// return to a course after another course has begun.
+    fn course_mut(
+        &mut self,
+        name: impl AsRef<str>,
+        breaks: &BreakConfig,
+    ) -> anyhow::Result<&mut Course> {
         let name = name.as_ref();
         if let Some(found_idx) =
             self.courses.iter().position(|course| course.name == name)
         {
-            return &mut self.courses[found_idx];
+            if found_idx + 1 != self.courses.len() {
+                anyhow::bail!(
+                    "course {name:?} appears again after course {:?} has begun",
+                    self.courses.last().unwrap().name
+                );
+            }
+            return Ok(&mut self.courses[found_idx]);
         }
-        let course = Course::new(name);
+        let mut course = Course::new(name);
+        course.break_between_sessions = breaks.between_sessions;
         self.courses.push(course);
-        self.courses.last_mut().unwrap()
+        Ok(self.courses.last_mut().unwrap())
     }
 
     /// Find a course by name.
@@ -171,27 +389,61 @@ impl Courses {
         self.courses.iter().find(|c| c.name == name)
     }
 
+    /// Find a course variant by name.
+    pub fn find_variant(&self, name: impl AsRef<str>) -> Option<&Course> {
+        let name = name.as_ref();
+        self.variants.iter().find(|c| c.name == name)
+    }
+
+    /// Return the course variants declared in the configuration.
+    pub fn variants(&self) -> &[Course] {
+        &self.variants
+    }
+
+    /// Replace these courses with the named variant alone, so that it can be
+    /// viewed like any other course.
+    pub fn into_variant(mut self, name: &str) -> anyhow::Result<Courses> {
+        let idx = self
+            .variants
+            .iter()
+            .position(|c| c.name == name)
+            .ok_or_else(|| anyhow::anyhow!("variant {name:?} not found"))?;
+        let mut courses = Courses {
+            courses: vec![self.variants.swap_remove(idx)],
+            ..Default::default()
+        };
+        courses.build_index();
+        Ok(courses)
+    }
+
     /// Find the slide generated from the given Chapter within these courses,
     /// returning the "path" to that slide.
+    ///
+    /// If the chapter's source appears in several places in the book, the
+    /// chapter's section number identifies which appearance this is.
     pub fn find_slide(
         &self,
         chapter: &Chapter,
     ) -> Option<(&Course, &Session, &Segment, &Slide)> {
-        let source_path = chapter.source_path.as_ref()?;
-
-        for course in self {
-            for session in course {
-                for segment in session {
-                    for slide in segment {
-                        if slide.source_paths.contains(source_path) {
-                            return Some((course, session, segment, slide));
-                        }
-                    }
-                }
-            }
-        }
+        let source_path = chapter.source_path.as_deref()?;
+        self.find_slides(source_path)
+            .find(|(_, _, _, slide)| slide.contains(chapter))
+    }
 
-        None
+    /// Find all slides containing the given source path. There is more than
+    /// one such slide when a chapter is reused in several places.
+    pub fn find_slides<'a>(
+        &'a self,
+        source_path: &Path,
+    ) -> impl Iterator<Item = (&'a Course, &'a Session, &'a Segment, &'a Slide)> + use<'a>
+    {
+        let positions = self.index.get(source_path).map_or(&[][..], Vec::as_slice);
+        positions.iter().map(|position| {
+            let course = &self.courses[position.course];
+            let session = &course.sessions[position.session];
+            let segment = &session.segments[position.segment];
+            (course, session, segment, &segment.slides[position.slide])
+        })
     }
 }
 
@@ -211,58 +463,99 @@ impl Course {
 
     /// Get a reference to a session, adding a new one if none by this name
     /// exists.
-    fn session_mut(&mut self, name: impl AsRef<str>) -> &mut Session {
+    ///
+    /// As with courses, it is an error to return to a session after another
+    /// session of the course has begun.
+    fn session_mut(
+        &mut self,
+        name: impl AsRef<str>,
+    ) -> anyhow::Result<&mut Session> {
         let name = name.as_ref();
         if let Some(found_idx) =
             self.sessions.iter().position(|session| session.name == name)
         {
-            return &mut self.sessions[found_idx];
+            if found_idx + 1 != self.sessions.len() {
+                anyhow::bail!(
+                    "session {name:?} appears again in course {:?} after session {:?}",
+                    self.name,
+                    self.sessions.last().unwrap().name
+                );
+            }
+            return Ok(&mut self.sessions[found_idx]);
         }
         let session = Session::new(name);
         self.sessions.push(session);
-        self.sessions.last_mut().unwrap()
+        Ok(self.sessions.last_mut().unwrap())
     }
 
-    /// Return the total duration of this course, as the sum of all segment
+    /// Return the total duration of this course, as the sum of all session
     /// durations.
     ///
-    /// This includes breaks between segments, but does not count time between
-    /// between sessions.
+    /// This includes breaks between segments and the breaks between sessions
+    /// (see [`Course::break_minutes`]).
     pub fn minutes(&self) -> u64 {
-        self.into_iter().map(|s| s.minutes()).sum()
+        let sessions: u64 = self.into_iter().map(|s| s.minutes()).sum();
+        sessions + self.break_minutes()
     }
 
-    /// Return the target duration of this course, as the sum of all segment
+    /// Return the target duration of this course, as the sum of all session
     /// target durations.
     ///
-    /// This includes breaks between segments, but does not count time between
-    /// sessions.
+    /// Like [`Course::minutes`], this includes the breaks between sessions.
     pub fn target_minutes(&self) -> u64 {
-        self.into_iter().map(|s| s.target_minutes()).sum()
+        let sessions: u64 = self.into_iter().map(|s| s.target_minutes()).sum();
+        sessions + self.break_minutes()
+    }
+
+    /// Return the total duration of the breaks between sessions of this course.
+    pub fn break_minutes(&self) -> u64 {
+        let breaks = self.sessions.len().saturating_sub(1) as u64;
+        breaks * self.break_between_sessions
+    }
+
+    /// Iterate over all segments in this course, across sessions.
+    pub fn segments(&self) -> impl Iterator<Item = &Segment> {
+        self.into_iter().flat_map(|session| session.into_iter())
+    }
+
+    /// Return the segment following the given segment in this course, if any.
+    pub fn next_segment(&self, segment: &Segment) -> Option<&Segment> {
+        self.segments().skip_while(|s| !std::ptr::eq(*s, segment)).nth(1)
+    }
+
+    /// Return the segment preceding the given segment in this course, if any.
+    pub fn previous_segment(&self, segment: &Segment) -> Option<&Segment> {
+        self.segments().take_while(|s| !std::ptr::eq(*s, segment)).last()
+    }
+
+    /// Return the duration of the break between sessions of this course.
+    pub fn break_between_sessions(&self) -> u64 {
+        self.break_between_sessions
     }
 
     /// Generate a Markdown schedule for this course, for placement at the given
-    /// path.
-    pub fn schedule(&self) -> String {
+    /// path, formatted for the given renderer.
+    pub fn schedule(&self, links: Links, renderer: &RendererOptions) -> String {
+        let rounding = renderer.rounding;
         let mut outline = String::from("Course schedule:\n");
-        for session in self {
+        for (idx, session) in self.into_iter().enumerate() {
+            if idx > 0 && self.break_between_sessions > 0 {
+                writeln!(
+                    &mut outline,
+                    " * Break ({})\n",
+                    duration(self.break_between_sessions, rounding)
+                )
+                .unwrap();
+            }
             writeln!(
                 &mut outline,
                 " * {} ({}, including breaks)\n",
                 session.name,
-                duration(session.minutes())
+                duration(session.minutes(), rounding)
             )
             .unwrap();
-            let mut segments = Table::new(["Segment".into(), "Duration".into()]);
-            for segment in session {
-                // Skip short segments (welcomes, wrap-up, etc.)
-                if segment.minutes() == 0 {
-                    continue;
-                }
-                segments
-                    .add_row([segment.name.clone(), duration(segment.minutes())]);
-            }
-            writeln!(&mut outline, "{}\n", segments).unwrap();
+            writeln!(&mut outline, "{}\n", session.segment_table(links, renderer))
+                .unwrap();
         }
         outline
     }
@@ -287,50 +580,142 @@ impl Session {
         &mut self,
         frontmatter: Frontmatter,
         chapter: &mut Chapter,
+        course_name: &str,
+        breaks: &BreakConfig,
+        cache: &FrontmatterCache,
     ) -> anyhow::Result<()> {
         let mut segment = Segment::new(&chapter.name);
-        segment.add_slide(frontmatter, chapter, false)?;
+        segment.target_minutes = frontmatter.target_minutes;
+        let break_after = frontmatter.break_after;
+        segment.add_slide(frontmatter, chapter, course_name, false, cache)?;
         for sub_chapter in &mut chapter.sub_items {
             let BookItem::Chapter(sub_chapter) = sub_chapter else {
                 continue;
             };
-            let (frontmatter, content) = split_frontmatter(sub_chapter)?;
+            let (frontmatter, content) = cache.split(sub_chapter)?;
             sub_chapter.content = content;
 
-            segment.add_slide(frontmatter, sub_chapter, true)?;
+            segment.add_slide(frontmatter, sub_chapter, course_name, true, cache)?;
+        }
+        if segment.minutes() > 0
+            && let Some(draft) = find_draft(chapter)
+        {
+            anyhow::bail!(
+                "draft chapter {:?} in timed segment {:?}; draft chapters have no \
+                 content to time",
+                draft.name,
+                segment.name
+            );
         }
+        segment.break_after =
+            break_after.unwrap_or_else(|| match breaks.after_exercise {
+                Some(minutes) if segment.ends_with_exercise() => minutes,
+                _ => breaks.between_segments,
+            });
         self.segments.push(segment);
         Ok(())
     }
 
     /// Generate a Markdown outline for this session, for placement at the given
-    /// path.
-    pub fn outline(&self) -> String {
-        let mut segments = Table::new(["Segment".into(), "Duration".into()]);
-        for segment in self {
-            // Skip short segments (welcomes, wrap-up, etc.)
-            if segment.minutes() == 0 {
-                continue;
-            }
-            segments.add_row([segment.name.clone(), duration(segment.minutes())]);
-        }
+    /// path, formatted for the given renderer.
+    pub fn outline(&self, links: Links, renderer: &RendererOptions) -> String {
+        let mut breaks: Vec<u64> =
+            self.timed_segments().filter_map(|(_, b)| b).collect();
+        breaks.sort();
+        breaks.dedup();
+        let duration = duration(self.minutes(), renderer.rounding);
+        let summary = match breaks.as_slice() {
+            [] => format!("This session should take about {duration}. It contains:"),
+            [minutes] => format!(
+                "Including {minutes} minute breaks, this session should take about \
+                 {duration}. It contains:"
+            ),
+            _ => format!(
+                "Including breaks, this session should take about {duration}. It \
+                 contains:"
+            ),
+        };
         format!(
-            "Including {BREAK_DURATION} minute breaks, this session should take about {}. It contains:\n\n{}",
-            duration(self.minutes()),
-            segments
+            "{summary}\n\n{}{}",
+            self.segment_table(links, renderer),
+            split_paragraph(
+                self.lecture_minutes(),
+                self.exercise_minutes(),
+                renderer.rounding
+            )
         )
     }
 
+    /// Return the segments of this session that take some time, each with
+    /// the duration of the break that follows it, if any.
+    ///
+    /// Short segments (welcomes, wrap-up, etc.) are omitted.
+    pub fn timed_segments(&self) -> impl Iterator<Item = (&Segment, Option<u64>)> {
+        let mut timed = self.into_iter().filter(|s| s.minutes() > 0).peekable();
+        std::iter::from_fn(move || {
+            let segment = timed.next()?;
+            let break_after = timed.peek().map(|_| segment.break_after);
+            Some((segment, break_after))
+        })
+    }
+
+    /// Generate a table of the timed segments in this session, with their
+    /// exercise time, and the breaks between them.
+    fn segment_table(&self, links: Links, renderer: &RendererOptions) -> Table<3> {
+        let rounding = renderer.rounding;
+        let mut segments =
+            Table::new(["Segment".into(), "Duration".into(), "Exercises".into()])
+                .with_style(renderer.tables);
+        for (segment, break_after) in self.timed_segments() {
+            let exercises = match segment.exercise_minutes() {
+                0 => String::new(),
+                minutes => duration(minutes, rounding),
+            };
+            segments.add_linked_row(
+                [
+                    segment.name.clone(),
+                    duration(segment.minutes(), rounding),
+                    exercises,
+                ],
+                links.cell(segment.source_path()),
+            );
+            if let Some(minutes) = break_after
+                && minutes > 0
+            {
+                segments.add_row([
+                    "Break".into(),
+                    duration(minutes, rounding),
+                    String::new(),
+                ]);
+            }
+        }
+        segments
+    }
+
+    /// Return the total duration of the exercise slides in this session,
+    /// excluding breaks.
+    pub fn exercise_minutes(&self) -> u64 {
+        self.into_iter().map(Segment::exercise_minutes).sum()
+    }
+
+    /// Return the total duration of the lecture slides in this session,
+    /// excluding breaks.
+    pub fn lecture_minutes(&self) -> u64 {
+        self.into_iter().map(Segment::lecture_minutes).sum()
+    }
+
     /// Return the total duration of this session.
     pub fn minutes(&self) -> u64 {
         let instructional_time: u64 = self.into_iter().map(|s| s.minutes()).sum();
-        if instructional_time == 0 {
-            return instructional_time;
-        }
-        let breaks = (self.into_iter().filter(|s| s.minutes() > 0).count() - 1)
-            as u64
-            * BREAK_DURATION;
-        instructional_time + breaks
+        instructional_time + self.break_minutes()
+    }
+
+    /// Return the total duration of the breaks in this session.
+    ///
+    /// Breaks are scheduled between segments that take some time, so a
+    /// session with no timed segments has no breaks.
+    pub fn break_minutes(&self) -> u64 {
+        self.timed_segments().filter_map(|(_, b)| b).sum()
     }
 
     /// Return the target duration of this session.
@@ -341,6 +726,50 @@ impl Session {
     }
 }
 
+/// A paragraph following an outline, splitting its duration into lecture and
+/// exercises, or nothing if there are no exercises.
+fn split_paragraph(lecture: u64, exercises: u64, rounding: u64) -> String {
+    if exercises == 0 {
+        return String::new();
+    }
+    format!(
+        "\nThis is {} of lecture and {} of exercises.\n",
+        duration(lecture, rounding),
+        duration(exercises, rounding)
+    )
+}
+
+/// Determine whether the chapter at the given source path is an exercise or
+/// its solution: an `exercise.md`, `solution.md`, `solutions.md` or
+/// `solutions-*.md` file, or any file in an `exercises` or `*-exercises`
+/// directory, such as `concurrency/sync-exercises/dining-philosophers.md`.
+fn is_exercise_path(path: &Path) -> bool {
+    let in_exercises = path.parent().is_some_and(|dir| {
+        dir.iter().any(|dir| {
+            dir.to_str()
+                .is_some_and(|dir| dir == "exercises" || dir.ends_with("-exercises"))
+        })
+    });
+    let is_exercise =
+        path.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| {
+            matches!(stem, "exercise" | "solution" | "solutions")
+                || stem.starts_with("solutions-")
+        });
+    in_exercises || is_exercise
+}
+
+/// Find a draft chapter, which has no source file, among the given chapter and
+/// its sub-chapters.
+fn find_draft(chapter: &Chapter) -> Option<&Chapter> {
+    if chapter.is_draft_chapter() {
+        return Some(chapter);
+    }
+    chapter.sub_items.iter().find_map(|item| match item {
+        BookItem::Chapter(sub_chapter) => find_draft(sub_chapter),
+        _ => None,
+    })
+}
+
 impl<'a> IntoIterator for &'a Session {
     type Item = &'a Segment;
     type IntoIter = std::slice::Iter<'a, Segment>;
@@ -361,13 +790,18 @@ impl Segment {
         &mut self,
         frontmatter: Frontmatter,
         chapter: &mut Chapter,
+        course_name: &str,
         recurse: bool,
+        cache: &FrontmatterCache,
     ) -> anyhow::Result<()> {
-        let mut slide = Slide::new(frontmatter, chapter);
+        let mut slide = Slide::new(&chapter.name);
+        slide.optional = frontmatter.optional;
+        slide.add_chapter(&frontmatter, chapter, course_name)?;
 
         if recurse {
-            slide.add_sub_chapters(chapter)?;
+            slide.add_sub_chapters(chapter, course_name, cache)?;
         }
+        slide.kind = frontmatter.kind.unwrap_or_else(|| slide.inferred_kind());
         self.slides.push(slide);
         Ok(())
     }
@@ -378,18 +812,78 @@ impl Segment {
         self.into_iter().map(|s| s.minutes()).sum()
     }
 
-    pub fn outline(&self) -> String {
-        let mut slides = Table::new(["Slide".into(), "Duration".into()]);
+    /// Return the total duration of the slides preceding the given slide in
+    /// this segment.
+    pub fn minutes_before(&self, slide: &Slide) -> u64 {
+        self.into_iter()
+            .take_while(|s| !std::ptr::eq(*s, slide))
+            .map(|s| s.minutes())
+            .sum()
+    }
+
+    /// Iterate over the quizzes in this segment, with the slide containing
+    /// each.
+    pub fn quizzes(&self) -> impl Iterator<Item = (&Slide, &Quiz)> {
+        self.into_iter()
+            .flat_map(|slide| slide.quizzes.iter().map(move |quiz| (slide, quiz)))
+    }
+
+    /// Return the duration of the break after this segment, if it is followed
+    /// by another timed segment in the session.
+    pub fn break_after(&self) -> u64 {
+        self.break_after
+    }
+
+    /// Determine whether the last slide in this segment is an exercise.
+    pub fn ends_with_exercise(&self) -> bool {
+        self.slides.last().is_some_and(Slide::is_exercise)
+    }
+
+    /// Return the total duration of the exercise slides in this segment.
+    pub fn exercise_minutes(&self) -> u64 {
+        self.into_iter().filter(|s| s.is_exercise()).map(|s| s.minutes()).sum()
+    }
+
+    /// Return the total duration of the lecture slides in this segment.
+    pub fn lecture_minutes(&self) -> u64 {
+        self.minutes() - self.exercise_minutes()
+    }
+
+    /// Return the `target_minutes` declared in this segment's frontmatter.
+    ///
+    /// Only the first segment of a session is expected to declare a target.
+    pub fn declared_target_minutes(&self) -> Option<u64> {
+        self.target_minutes
+    }
+
+    /// Return the source path of the first chapter in this segment.
+    pub fn source_path(&self) -> Option<&Path> {
+        self.slides.first().and_then(|slide| slide.source_path())
+    }
+
+    /// Generate a Markdown outline for this segment, formatted for the given
+    /// renderer.
+    pub fn outline(&self, links: Links, renderer: &RendererOptions) -> String {
+        let rounding = renderer.rounding;
+        let mut slides = Table::new(["Slide".into(), "Duration".into()])
+            .with_style(renderer.tables);
         for slide in self {
             if slide.minutes() == 0 {
                 continue;
             }
-            slides.add_row([slide.name.clone(), duration(slide.minutes())]);
+            slides.add_linked_row(
+                [slide.name.clone(), duration(slide.minutes(), rounding)],
+                links.cell(slide.source_path()),
+            );
         }
         format!(
-            "This segment should take about {}. It contains:\n\n{}",
-            duration(self.minutes()),
-            slides,
+            "This segment should take about {}. It contains:\n\n{slides}{}",
+            duration(self.minutes(), rounding),
+            split_paragraph(
+                self.lecture_minutes(),
+                self.exercise_minutes(),
+                rounding
+            )
         )
     }
 }
@@ -404,45 +898,98 @@ impl<'a> IntoIterator for &'a Segment {
 }
 
 impl Slide {
-    fn new(frontmatter: Frontmatter, chapter: &Chapter) -> Self {
-        let mut slide = Self { name: chapter.name.clone(), ..Default::default() };
-        slide.add_frontmatter(&frontmatter);
-        slide.push_source_path(&chapter.source_path);
-        slide
-    }
-
-    fn add_frontmatter(&mut self, frontmatter: &Frontmatter) {
-        self.minutes += frontmatter.minutes.unwrap_or(0);
+    fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), ..Default::default() }
     }
 
-    fn push_source_path(&mut self, source_path: &Option<PathBuf>) {
-        if let Some(source_path) = &source_path {
+    /// Add a chapter to this slide, using the minutes its frontmatter gives for
+    /// the given course.
+    fn add_chapter(
+        &mut self,
+        frontmatter: &Frontmatter,
+        chapter: &Chapter,
+        course_name: &str,
+    ) -> anyhow::Result<()> {
+        let minutes = match &frontmatter.minutes {
+            Some(minutes) => minutes.for_course(course_name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{:?}: 'minutes' does not include course {course_name:?}",
+                    chapter.source_path
+                )
+            })?,
+            None => 0,
+        };
+        let quizzes = parse_quizzes(&chapter.content)
+            .map_err(|e| anyhow::anyhow!("{:?}: {e}", chapter.source_path))?;
+        self.minutes += minutes;
+        self.quizzes.extend(quizzes);
+        for concept in &frontmatter.requires {
+            if !self.requires.contains(concept) {
+                self.requires.push(concept.clone());
+            }
+        }
+        for concept in &frontmatter.provides {
+            if !self.provides.contains(concept) {
+                self.provides.push(concept.clone());
+            }
+        }
+        if let Some(source_path) = &chapter.source_path {
             self.source_paths.push(source_path.clone());
+            self.chapters.push(SlideChapter {
+                source_path: source_path.clone(),
+                number: chapter.number.clone(),
+                minutes,
+            });
         }
+        Ok(())
     }
 
     /// Add sub-chapters of this chapter to this slide (recursively).
-    fn add_sub_chapters(&mut self, chapter: &mut Chapter) -> anyhow::Result<()> {
+    fn add_sub_chapters(
+        &mut self,
+        chapter: &mut Chapter,
+        course_name: &str,
+        cache: &FrontmatterCache,
+    ) -> anyhow::Result<()> {
         for sub_slide in &mut chapter.sub_items {
             let BookItem::Chapter(sub_slide) = sub_slide else {
                 continue;
             };
-            let (frontmatter, content) = split_frontmatter(sub_slide)?;
+            let (frontmatter, content) = cache.split(sub_slide)?;
             sub_slide.content = content;
 
-            if frontmatter.course.is_some() || frontmatter.session.is_some() {
+            if frontmatter.course.is_some()
+                || frontmatter.session.is_some()
+                || frontmatter.break_after.is_some()
+                || frontmatter.optional
+                || frontmatter.kind.is_some()
+            {
                 anyhow::bail!(
-                    "{:?}: sub-slides may not have 'course' or 'session' set",
+                    "{:?}: sub-slides may not have 'course', 'session', 'break_after', 'optional' or 'kind' set",
                     sub_slide.path
                 );
             }
-            self.add_frontmatter(&frontmatter);
-            self.push_source_path(&sub_slide.source_path);
-            self.add_sub_chapters(sub_slide)?;
+            self.add_chapter(&frontmatter, sub_slide, course_name)?;
+            self.add_sub_chapters(sub_slide, course_name, cache)?;
         }
         Ok(())
     }
 
+    /// Return the source path of the first chapter in this slide.
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source_paths.first().map(|p| p.as_path())
+    }
+
+    /// Determine whether the given chapter is part of this slide.
+    pub fn contains(&self, chapter: &Chapter) -> bool {
+        self.chapters.iter().any(|c| {
+            chapter.source_path.as_ref() == Some(&c.source_path)
+                && (chapter.number.is_none()
+                    || c.number.is_none()
+                    || chapter.number == c.number)
+        })
+    }
+
     /// Determine whether the given chapter is a sub-chapter of this slide.
     pub fn is_sub_chapter(&self, chapter: &Chapter) -> bool {
         // The first `source_path` in the slide is the "parent" chapter, so anything
@@ -454,4 +1001,345 @@ impl Slide {
     pub fn minutes(&self) -> u64 {
         self.minutes
     }
+
+    /// Return the quizzes in this slide.
+    pub fn quizzes(&self) -> &[Quiz] {
+        &self.quizzes
+    }
+
+    /// Determine whether this slide is left out of course variants.
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    /// Return whether this slide is lecture material or an exercise.
+    pub fn kind(&self) -> SlideKind {
+        self.kind
+    }
+
+    /// Determine whether this slide is an exercise.
+    pub fn is_exercise(&self) -> bool {
+        self.kind == SlideKind::Exercise
+    }
+
+    /// The kind of a slide without `kind` in its frontmatter: an exercise if it
+    /// contains an exercise or solution chapter (see [`is_exercise_path`]).
+    fn inferred_kind(&self) -> SlideKind {
+        if self.source_paths.iter().any(|path| is_exercise_path(path)) {
+            SlideKind::Exercise
+        } else {
+            SlideKind::Lecture
+        }
+    }
+
+    /// Return the concepts which must be introduced before this slide.
+    pub fn requires(&self) -> &[String] {
+        &self.requires
+    }
+
+    /// Return the concepts which this slide introduces.
+    pub fn provides(&self) -> &[String] {
+        &self.provides
+    }
+
+    /// Return the minutes declared by each chapter in this slide, in the same
+    /// order as `source_paths`.
+    pub fn chapter_minutes(&self) -> impl Iterator<Item = (&Path, u64)> {
+        self.chapters.iter().map(|c| (c.source_path.as_path(), c.minutes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::chapter;
+
+    fn extract(book: Book, config: &Config) -> Courses {
+        Courses::extract_structure(book, config).unwrap().0
+    }
+
+    fn test_book() -> Book {
+        let mut book = Book::new();
+        book.push_item(chapter(
+            "welcome",
+            "---\ncourse: C\nsession: S\n---\n",
+            Vec::new(),
+        ));
+        book.push_item(chapter(
+            "one",
+            "---\nminutes: 20\n---\n",
+            vec![chapter("exercise", "---\nminutes: 30\n---\n", Vec::new())],
+        ));
+        book.push_item(chapter(
+            "two",
+            "---\nminutes: 30\nbreak_after: 5\n---\n",
+            Vec::new(),
+        ));
+        book.push_item(chapter("three", "---\nminutes: 30\n---\n", Vec::new()));
+        book.push_item(chapter("afternoon", "---\nsession: T\n---\n", Vec::new()));
+        book
+    }
+
+    #[test]
+    fn shared_chapter() {
+        let shared = "---\nminutes: {A: 10, B: 5}\n---\n";
+        let mut book = Book::new();
+        for (idx, course) in ["A", "B"].into_iter().enumerate() {
+            let mut welcome = chapter(
+                course,
+                &format!("---\ncourse: {course}\nsession: S\n---\n"),
+                Vec::new(),
+            );
+            welcome.number = Some(SectionNumber(vec![idx as u32 * 2 + 1]));
+            book.push_item(welcome);
+            let mut shared = chapter("shared", shared, Vec::new());
+            shared.number = Some(SectionNumber(vec![idx as u32 * 2 + 2]));
+            book.push_item(shared);
+        }
+        let (courses, book) =
+            Courses::extract_structure(book, &Config::default()).unwrap();
+
+        let chapters: Vec<_> = book
+            .iter()
+            .filter_map(|item| match item {
+                BookItem::Chapter(c) if c.name == "shared" => Some(c),
+                _ => None,
+            })
+            .collect();
+        let (course, _, _, slide) = courses.find_slide(chapters[0]).unwrap();
+        assert_eq!((course.name.as_str(), slide.minutes()), ("A", 10));
+        let (course, _, _, slide) = courses.find_slide(chapters[1]).unwrap();
+        assert_eq!((course.name.as_str(), slide.minutes()), ("B", 5));
+        assert_eq!(courses.find_slides(Path::new("shared.md")).count(), 2);
+    }
+
+    #[test]
+    fn per_course_minutes_missing_course() {
+        let mut book = Book::new();
+        book.push_item(chapter(
+            "welcome",
+            "---\ncourse: C\nsession: S\nminutes: {A: 10}\n---\n",
+            Vec::new(),
+        ));
+        assert!(Courses::extract_structure(book, &Config::default()).is_err());
+    }
+
+    #[test]
+    fn nesting_rules() {
+        let error = |book: Book| {
+            Courses::extract_structure(book, &Config::default())
+                .unwrap_err()
+                .to_string()
+        };
+
+        // Part titles and separators may divide sessions, but not segments.
+        let mut book = test_book();
+        book.sections.insert(4, BookItem::PartTitle("Afternoon".into()));
+        assert!(Courses::extract_structure(book, &Config::default()).is_ok());
+        let mut book = test_book();
+        book.sections.insert(2, BookItem::Separator);
+        assert!(error(book).contains("separator inside session \"S\""));
+
+        let mut book = test_book();
+        let BookItem::Chapter(one) = &mut book.sections[1] else {
+            unreachable!();
+        };
+        let draft = Chapter::new_draft("Later", Vec::new());
+        one.sub_items.push(BookItem::Chapter(draft));
+        assert!(error(book).contains("draft chapter \"Later\""));
+
+        let mut book = test_book();
+        book.push_item(chapter("evening", "---\nsession: S\n---\n", Vec::new()));
+        assert!(error(book).contains("session \"S\" appears again"));
+
+        let mut book = test_book();
+        book.push_item(chapter(
+            "d",
+            "---\ncourse: D\nsession: S\n---\n",
+            Vec::new(),
+        ));
+        book.push_item(chapter(
+            "c",
+            "---\ncourse: C\nsession: U\n---\n",
+            Vec::new(),
+        ));
+        assert!(
+            error(book).contains("course \"C\" appears again after course \"D\"")
+        );
+    }
+
+    #[test]
+    fn default_breaks() {
+        let courses = extract(test_book(), &Config::default());
+        let session = &courses.courses[0].sessions[0];
+        assert_eq!(session.break_minutes(), 15);
+        assert_eq!(session.minutes(), 125);
+        assert_eq!(
+            session.outline(Links::Plain, &Config::default().renderer("html")),
+            "Including breaks, this session should take about 2 hours and 5 \
+             minutes. It contains:\n\n\
+             | Segment | Duration | Exercises |\n| - | - | - |\n\
+             | one | 50 minutes | 30 minutes |\n| Break | 10 minutes |  |\n\
+             | two | 30 minutes |  |\n| Break | 5 minutes |  |\n\
+             | three | 30 minutes |  |\n\n\
+             This is 1 hour and 20 minutes of lecture and 30 minutes of \
+             exercises.\n"
+        );
+    }
+
+    #[test]
+    fn slide_kinds() {
+        let courses = extract(test_book(), &Config::default());
+        let session = &courses.courses[0].sessions[0];
+        assert_eq!(session.exercise_minutes(), 30);
+        assert_eq!(session.lecture_minutes(), 80);
+        assert!(session.segments[1].slides[1].is_exercise());
+
+        let mut book = test_book();
+        book.push_item(chapter(
+            "lab",
+            "---\nminutes: 15\nkind: exercise\n---\n",
+            Vec::new(),
+        ));
+        book.push_item(chapter(
+            "exercise",
+            "---\nminutes: 5\nkind: lecture\n---\n",
+            Vec::new(),
+        ));
+        let courses = extract(book, &Config::default());
+        let afternoon = &courses.courses[0].sessions[1];
+        assert_eq!(
+            (afternoon.lecture_minutes(), afternoon.exercise_minutes()),
+            (5, 15)
+        );
+        assert!(afternoon.segments[1].ends_with_exercise());
+        assert!(!afternoon.segments[2].ends_with_exercise());
+    }
+
+    #[test]
+    fn exercise_paths() {
+        for path in [
+            "types/exercise.md",
+            "types/solution.md",
+            "concurrency/sync-exercises/solutions.md",
+            "concurrency/sync-exercises/dining-philosophers.md",
+            "exercises/chromium/build-rules.md",
+            "exercises/bare-metal/solutions-morning.md",
+        ] {
+            assert!(is_exercise_path(Path::new(path)), "{path}");
+        }
+        for path in ["concurrency/sync-exercises.md", "types/exercises-intro.md"] {
+            assert!(!is_exercise_path(Path::new(path)), "{path}");
+        }
+    }
+
+    #[test]
+    fn segment_navigation() {
+        let courses = extract(test_book(), &Config::default());
+        let course = &courses.courses[0];
+        let names = |segment: Option<&Segment>| segment.map(|s| s.name.clone());
+        let two = &course.sessions[0].segments[2];
+        assert_eq!(names(course.previous_segment(two)), Some("one".into()));
+        assert_eq!(names(course.next_segment(two)), Some("three".into()));
+        let afternoon = &course.sessions[1].segments[0];
+        assert_eq!(names(course.previous_segment(afternoon)), Some("three".into()));
+        assert_eq!(names(course.next_segment(afternoon)), None);
+    }
+
+    #[test]
+    fn linked_outline() {
+        let courses = extract(test_book(), &Config::default());
+        let segment = &courses.courses[0].sessions[0].segments[1];
+        let html = Config::default().renderer("html");
+        let linked =
+            segment.outline(Links::RelativeTo(Path::new("welcome.md")), &html);
+        assert_eq!(
+            linked,
+            "This segment should take about 50 minutes. It contains:\n\n\
+             | Slide | Duration |  |\n| - | - | - |\n\
+             | one | 20 minutes | [→](./one.md) |\n\
+             | exercise | 30 minutes | [→](./exercise.md) |\n\n\
+             This is 20 minutes of lecture and 30 minutes of exercises.\n"
+        );
+        // Apart from the links, the outline is the same as the plain one, so
+        // that the same translations apply.
+        let plain = segment.outline(Links::Plain, &html);
+        for (linked, plain) in linked.lines().zip(plain.lines()) {
+            assert!(linked.starts_with(plain.trim_end_matches('|')), "{linked}");
+        }
+    }
+
+    #[test]
+    fn outline_rounding() {
+        let mut config = Config::default();
+        config.timing.rounding = 15;
+        let courses = extract(test_book(), &config);
+        let segment = &courses.courses[0].sessions[0].segments[1];
+        let outline = segment.outline(Links::Plain, &config.renderer("html"));
+        assert!(outline.starts_with("This segment should take about 1 hour."));
+        assert!(outline.contains("| one | 30 minutes |\n"), "{outline}");
+        assert!(outline.contains("| exercise | 30 minutes |\n"), "{outline}");
+    }
+
+    #[test]
+    fn variant() {
+        let mut book = test_book();
+        book.push_item(chapter(
+            "four",
+            "---\nminutes: 10\noptional: true\n---\n",
+            Vec::new(),
+        ));
+        let mut config = Config::default();
+        config.variants.insert(
+            "Short".into(),
+            VariantConfig {
+                course: "C".into(),
+                drop_segments: vec!["two".into()],
+                include_optional: false,
+                target_minutes: [("S".into(), 90)].into(),
+            },
+        );
+        let courses = extract(book, &config);
+        assert_eq!(courses.courses[0].minutes(), 135);
+        let variant = courses.find_variant("Short").unwrap();
+        let session = &variant.sessions[0];
+        let names: Vec<_> = session.into_iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["welcome", "one", "three"]);
+        assert_eq!((session.minutes(), session.target_minutes()), (90, 90));
+        assert_eq!(variant.sessions[1].segments.len(), 1);
+
+        let courses = courses.into_variant("Short").unwrap();
+        assert_eq!(courses.courses[0].name, "Short");
+
+        config.variants.get_mut("Short").unwrap().drop_segments =
+            vec!["bogus".into()];
+        assert!(Courses::extract_structure(test_book(), &config).is_err());
+    }
+
+    #[test]
+    fn configured_breaks() {
+        let config = Config {
+            breaks: BreakConfig {
+                between_segments: 5,
+                after_exercise: Some(15),
+                between_sessions: 60,
+            },
+            ..Default::default()
+        };
+        let courses = extract(test_book(), &config);
+        let course = &courses.courses[0];
+        assert_eq!(course.break_between_sessions(), 60);
+        let session = &course.sessions[0];
+        let breaks: Vec<_> = session.timed_segments().map(|(_, b)| b).collect();
+        assert_eq!(breaks, vec![Some(15), Some(5), None]);
+        assert_eq!(session.minutes(), 130);
+        // The course's two sessions are separated by a break.
+        assert_eq!(course.break_minutes(), 60);
+        assert_eq!(course.minutes(), 190);
+        assert!(
+            course
+                .schedule(Links::Plain, &Config::default().renderer("html"))
+                .contains(" * Break (1 hour)\n")
+        );
+    }
 }
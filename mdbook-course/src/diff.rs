@@ -0,0 +1,370 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Differences between two versions of the course structure, such as before
+//! and after a pull request.
+//!
+//! Courses, sessions and segments are identified by name. Slides are
+//! identified by the source path of their first chapter within a course, so a
+//! slide whose chapter appears in a different segment has moved.
+
+use crate::course::{Course, Courses, Session};
+use crate::markdown::{Table, duration};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Whether a session's duration is within its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetStatus {
+    Within,
+    Over(u64),
+    Under(u64),
+}
+
+impl TargetStatus {
+    fn new(session: &Session, slop: u64) -> Self {
+        let (actual, target) = (session.minutes(), session.target_minutes());
+        if actual > target + slop {
+            TargetStatus::Over(actual - target)
+        } else if actual + slop < target {
+            TargetStatus::Under(target - actual)
+        } else {
+            TargetStatus::Within
+        }
+    }
+}
+
+/// The location of a slide within a course.
+#[derive(Debug, PartialEq, Eq)]
+struct SlideLocation<'a> {
+    name: &'a str,
+    session: &'a str,
+    segment: &'a str,
+    minutes: u64,
+}
+
+impl SlideLocation<'_> {
+    fn describe(&self) -> String {
+        format!("{} / {}", self.session, self.segment)
+    }
+}
+
+/// Collect the location of each slide in a course, keyed by source path.
+fn slide_locations(course: &Course) -> BTreeMap<&Path, SlideLocation<'_>> {
+    let mut slides = BTreeMap::new();
+    for session in course {
+        for segment in session {
+            for slide in segment {
+                if let Some(source_path) = slide.source_path() {
+                    slides.insert(
+                        source_path,
+                        SlideLocation {
+                            name: &slide.name,
+                            session: &session.name,
+                            segment: &segment.name,
+                            minutes: slide.minutes(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+    slides
+}
+
+/// Format a change in minutes, such as "+5 minutes".
+fn delta(before: u64, after: u64) -> String {
+    let (sign, minutes) =
+        if after >= before { ('+', after - before) } else { ('-', before - after) };
+    let unit = if minutes == 1 { "minute" } else { "minutes" };
+    format!("{sign}{minutes} {unit}")
+}
+
+/// Describe the differences between the `base` and `head` courses as Markdown,
+/// suitable for a pull request comment.
+///
+/// Sessions are within their target if they are no more than `slop` minutes
+/// longer or shorter than it. Durations are rounded to `rounding` minutes.
+pub fn diff(base: &Courses, head: &Courses, slop: u64, rounding: u64) -> String {
+    let mut out = String::from("## Course Schedule Changes\n\n");
+    let mut changed = false;
+
+    for course in base {
+        if head.find_course(&course.name).is_none() {
+            changed = true;
+            writeln!(out, "### {}\n\n_Course removed._\n", course.name).unwrap();
+        }
+    }
+    for head_course in head {
+        let course_diff = match base.find_course(&head_course.name) {
+            Some(base_course) => {
+                diff_course(base_course, head_course, slop, rounding)
+            }
+            None => Some(format!(
+                "_Course added, taking {}._\n\n",
+                duration(head_course.minutes(), rounding)
+            )),
+        };
+        if let Some(course_diff) = course_diff {
+            changed = true;
+            write!(out, "### {}\n\n{course_diff}", head_course.name).unwrap();
+        }
+    }
+
+    if !changed {
+        out.push_str("This pull request does not change the course schedule.\n");
+    }
+    out
+}
+
+/// Describe the differences between two versions of a course, or return None
+/// if there are none.
+fn diff_course(
+    base: &Course,
+    head: &Course,
+    slop: u64,
+    rounding: u64,
+) -> Option<String> {
+    let mut out = String::new();
+
+    // Sessions, including any which were added or removed.
+    let mut sessions = Table::new([
+        "Session".into(),
+        "Before".into(),
+        "After".into(),
+        "Change".into(),
+    ]);
+    let mut has_sessions = false;
+    let mut warnings = Vec::new();
+    let names = base.into_iter().chain(head).map(|s| s.name.as_str());
+    let mut seen = Vec::new();
+    for name in names {
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+        let base_session = base.into_iter().find(|s| s.name == name);
+        let head_session = head.into_iter().find(|s| s.name == name);
+        let before = base_session.map_or(0, |s| s.minutes());
+        let after = head_session.map_or(0, |s| s.minutes());
+        if before != after || base_session.is_none() || head_session.is_none() {
+            has_sessions = true;
+            sessions.add_row([
+                name.to_string(),
+                base_session.map_or("-".into(), |_| duration(before, rounding)),
+                head_session.map_or("-".into(), |_| duration(after, rounding)),
+                delta(before, after),
+            ]);
+        }
+
+        let Some(head_session) = head_session else { continue };
+        let base_status = base_session
+            .map_or(TargetStatus::Within, |s| TargetStatus::new(s, slop));
+        let head_status = TargetStatus::new(head_session, slop);
+        if std::mem::discriminant(&head_status)
+            == std::mem::discriminant(&base_status)
+        {
+            continue;
+        }
+        let target = duration(head_session.target_minutes(), rounding);
+        match head_status {
+            TargetStatus::Over(by) => warnings.push(format!(
+                "\u{23f0} **{name}** is now {} over its target of {target}.",
+                duration(by, rounding)
+            )),
+            TargetStatus::Under(by) => warnings.push(format!(
+                "\u{23f0} **{name}** is now {} under its target of {target}.",
+                duration(by, rounding)
+            )),
+            TargetStatus::Within => warnings
+                .push(format!("\u{2705} **{name}** is now within its target.")),
+        }
+    }
+
+    // Segments which exist in both versions, and whose duration changed.
+    let mut segments = Table::new([
+        "Session".into(),
+        "Segment".into(),
+        "Before".into(),
+        "After".into(),
+        "Change".into(),
+    ]);
+    let mut has_segments = false;
+    for head_session in head {
+        let Some(base_session) =
+            base.into_iter().find(|s| s.name == head_session.name)
+        else {
+            continue;
+        };
+        for head_segment in head_session {
+            let Some(base_segment) =
+                base_session.into_iter().find(|s| s.name == head_segment.name)
+            else {
+                continue;
+            };
+            let (before, after) = (base_segment.minutes(), head_segment.minutes());
+            if before != after {
+                has_segments = true;
+                segments.add_row([
+                    head_session.name.clone(),
+                    head_segment.name.clone(),
+                    duration(before, rounding),
+                    duration(after, rounding),
+                    delta(before, after),
+                ]);
+            }
+        }
+    }
+
+    // Slides which were added, removed or moved.
+    let base_slides = slide_locations(base);
+    let head_slides = slide_locations(head);
+    let mut slides = Vec::new();
+    for (source_path, slide) in &head_slides {
+        match base_slides.get(source_path) {
+            None => slides.push(format!(
+                "* Added _{}_ (`{}`) to {}, taking {}",
+                slide.name,
+                source_path.display(),
+                slide.describe(),
+                duration(slide.minutes, rounding)
+            )),
+            Some(base_slide)
+                if (base_slide.session, base_slide.segment)
+                    != (slide.session, slide.segment) =>
+            {
+                slides.push(format!(
+                    "* Moved _{}_ (`{}`) from {} to {}",
+                    slide.name,
+                    source_path.display(),
+                    base_slide.describe(),
+                    slide.describe()
+                ))
+            }
+            _ => {}
+        }
+    }
+    for (source_path, slide) in &base_slides {
+        if !head_slides.contains_key(source_path) {
+            slides.push(format!(
+                "* Removed _{}_ (`{}`) from {}",
+                slide.name,
+                source_path.display(),
+                slide.describe()
+            ));
+        }
+    }
+
+    if !has_sessions && !has_segments && slides.is_empty() && warnings.is_empty() {
+        return None;
+    }
+
+    let (before, after) = (base.minutes(), head.minutes());
+    if before != after {
+        writeln!(
+            out,
+            "_{} \u{2192} {} ({})_\n",
+            duration(before, rounding),
+            duration(after, rounding),
+            delta(before, after)
+        )
+        .unwrap();
+    }
+    for warning in &warnings {
+        writeln!(out, "{warning}\n").unwrap();
+    }
+    if has_sessions {
+        writeln!(out, "#### Sessions\n\n{sessions}").unwrap();
+    }
+    if has_segments {
+        writeln!(out, "#### Segments\n\n{segments}").unwrap();
+    }
+    if !slides.is_empty() {
+        writeln!(out, "#### Slides\n\n{}\n", slides.join("\n")).unwrap();
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{chapter, courses};
+
+    #[test]
+    fn unchanged() {
+        let book = || {
+            courses(vec![chapter(
+                "a",
+                "---\ncourse: C\nsession: S\nminutes: 5\n---\n",
+                Vec::new(),
+            )])
+        };
+        assert_eq!(
+            diff(&book(), &book(), 5, 5),
+            "## Course Schedule Changes\n\n\
+             This pull request does not change the course schedule.\n"
+        );
+    }
+
+    #[test]
+    fn changes() {
+        let base = courses(vec![
+            chapter(
+                "a",
+                "---\ncourse: C\nsession: S\nminutes: 5\ntarget_minutes: 30\n---\n",
+                vec![chapter("x", "---\nminutes: 5\n---\n", Vec::new())],
+            ),
+            chapter(
+                "b",
+                "---\nminutes: 5\n---\n",
+                vec![chapter("y", "---\nminutes: 5\n---\n", Vec::new())],
+            ),
+        ]);
+        let head = courses(vec![
+            chapter(
+                "a",
+                "---\ncourse: C\nsession: S\nminutes: 5\ntarget_minutes: 30\n---\n",
+                vec![chapter("z", "---\nminutes: 15\n---\n", Vec::new())],
+            ),
+            chapter(
+                "b",
+                "---\nminutes: 5\n---\n",
+                vec![
+                    chapter("y", "---\nminutes: 5\n---\n", Vec::new()),
+                    chapter("x", "---\nminutes: 5\n---\n", Vec::new()),
+                ],
+            ),
+        ]);
+        assert_eq!(
+            diff(&base, &head, 5, 5),
+            "## Course Schedule Changes\n\n\
+             ### C\n\n\
+             _30 minutes \u{2192} 45 minutes (+15 minutes)_\n\n\
+             \u{23f0} **S** is now 15 minutes over its target of 30 minutes.\n\n\
+             #### Sessions\n\n\
+             | Session | Before | After | Change |\n\
+             | - | - | - | - |\n\
+             | S | 30 minutes | 45 minutes | +15 minutes |\n\n\
+             #### Segments\n\n\
+             | Session | Segment | Before | After | Change |\n\
+             | - | - | - | - | - |\n\
+             | S | a | 10 minutes | 20 minutes | +10 minutes |\n\
+             | S | b | 10 minutes | 15 minutes | +5 minutes |\n\n\
+             #### Slides\n\n\
+             * Moved _x_ (`x.md`) from S / a to S / b\n\
+             * Added _z_ (`z.md`) to S / a, taking 15 minutes\n\n"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Machine-readable representation of the course structure.
+//!
+//! The types in this module mirror the hierarchy in [`crate::course`], but
+//! include computed values (durations, breaks) and are serialized in a stable
+//! format. Any change to the shape of the serialized data must increment
+//! [`SCHEMA_VERSION`].
+
+use crate::course::{Course, Courses, Segment, Session, Slide};
+use serde::Serialize;
+use std::path::Path;
+
+/// Version of the serialized schema.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Output format for an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+}
+
+impl std::str::FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            _ => anyhow::bail!("unknown export format {s:?}"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct CoursesExport<'a> {
+    pub schema_version: u32,
+    pub courses: Vec<CourseExport<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CourseExport<'a> {
+    pub name: &'a str,
+    pub minutes: u64,
+    pub target_minutes: u64,
+    pub break_minutes: u64,
+    pub break_between_sessions: u64,
+    pub sessions: Vec<SessionExport<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SessionExport<'a> {
+    pub name: &'a str,
+    pub minutes: u64,
+    pub target_minutes: u64,
+    pub break_minutes: u64,
+    pub segments: Vec<SegmentExport<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SegmentExport<'a> {
+    pub name: &'a str,
+    pub minutes: u64,
+    /// The break after this segment, if it is followed by another timed
+    /// segment.
+    pub break_after: Option<u64>,
+    pub slides: Vec<SlideExport<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SlideExport<'a> {
+    pub name: &'a str,
+    pub minutes: u64,
+    pub source_paths: Vec<&'a Path>,
+}
+
+impl<'a> CoursesExport<'a> {
+    pub fn new(courses: &'a Courses) -> Self {
+        CoursesExport {
+            schema_version: SCHEMA_VERSION,
+            courses: courses.into_iter().map(CourseExport::new).collect(),
+        }
+    }
+
+    /// Serialize this export in the given format.
+    pub fn to_string(&self, format: Format) -> anyhow::Result<String> {
+        Ok(match format {
+            Format::Json => serde_json::to_string_pretty(self)? + "\n",
+            Format::Yaml => serde_yaml::to_string(self)?,
+        })
+    }
+}
+
+impl<'a> CourseExport<'a> {
+    pub fn new(course: &'a Course) -> Self {
+        CourseExport {
+            name: &course.name,
+            minutes: course.minutes(),
+            target_minutes: course.target_minutes(),
+            break_minutes: course
+                .into_iter()
+                .map(|s| s.break_minutes())
+                .sum::<u64>()
+                + course.break_minutes(),
+            break_between_sessions: course.break_between_sessions(),
+            sessions: course.into_iter().map(SessionExport::new).collect(),
+        }
+    }
+}
+
+impl<'a> SessionExport<'a> {
+    pub fn new(session: &'a Session) -> Self {
+        SessionExport {
+            name: &session.name,
+            minutes: session.minutes(),
+            target_minutes: session.target_minutes(),
+            break_minutes: session.break_minutes(),
+            segments: session
+                .into_iter()
+                .map(|segment| {
+                    let break_after = session
+                        .timed_segments()
+                        .find(|(s, _)| std::ptr::eq(*s, segment))
+                        .and_then(|(_, b)| b);
+                    SegmentExport::new(segment, break_after)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a> SegmentExport<'a> {
+    pub fn new(segment: &'a Segment, break_after: Option<u64>) -> Self {
+        SegmentExport {
+            name: &segment.name,
+            minutes: segment.minutes(),
+            break_after,
+            slides: segment.into_iter().map(SlideExport::new).collect(),
+        }
+    }
+}
+
+impl<'a> SlideExport<'a> {
+    pub fn new(slide: &'a Slide) -> Self {
+        SlideExport {
+            name: &slide.name,
+            minutes: slide.minutes(),
+            source_paths: slide.source_paths.iter().map(|p| p.as_path()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{chapter, courses};
+
+    #[test]
+    fn export_json() {
+        let courses = courses([
+            chapter(
+                "one",
+                "---\ncourse: C\nsession: S\ntarget_minutes: 30\nminutes: 5\n---\n",
+                vec![chapter("one-a", "---\nminutes: 3\n---\n", Vec::new())],
+            ),
+            chapter("two", "---\nminutes: 7\n---\n", Vec::new()),
+        ]);
+
+        let json = CoursesExport::new(&courses).to_string(Format::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        let session = &value["courses"][0]["sessions"][0];
+        assert_eq!(session["name"], "S");
+        assert_eq!(session["minutes"], 25);
+        assert_eq!(session["target_minutes"], 30);
+        assert_eq!(session["break_minutes"], 10);
+        assert_eq!(session["segments"][0]["break_after"], 10);
+        assert_eq!(session["segments"][1]["break_after"], serde_json::Value::Null);
+        let slide = &session["segments"][0]["slides"][1];
+        assert_eq!(slide["name"], "one-a");
+        assert_eq!(slide["minutes"], 3);
+        assert_eq!(slide["source_paths"][0], "one-a.md");
+    }
+}
@@ -12,31 +12,592 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::markdown::suggest;
 use anyhow::Context;
 use matter::matter;
-use mdbook::book::Chapter;
-use serde::Deserialize;
+use mdbook::book::{Book, BookItem, Chapter};
+use serde::{Deserialize, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
-#[derive(Deserialize, Debug, Default)]
+/// The fields that may appear in frontmatter. This must be kept in sync with
+/// the fields of [`Frontmatter`].
+const FIELDS: &[&str] = &[
+    "minutes",
+    "target_minutes",
+    "course",
+    "session",
+    "break_after",
+    "requires",
+    "provides",
+    "optional",
+    "kind",
+];
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Frontmatter {
-    pub minutes: Option<u64>,
+    pub minutes: Option<Minutes>,
     pub target_minutes: Option<u64>,
     pub course: Option<String>,
     pub session: Option<String>,
+    pub break_after: Option<u64>,
+    /// Concepts which must be introduced before this chapter.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Concepts which this chapter introduces.
+    #[serde(default)]
+    pub provides: Vec<String>,
+    /// Whether this slide is left out of course variants.
+    #[serde(default)]
+    pub optional: bool,
+    /// The kind of slide, if not inferred from its source paths.
+    pub kind: Option<SlideKind>,
+}
+
+/// The `kind` field of frontmatter, distinguishing hands-on exercises from
+/// lecture material.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlideKind {
+    #[default]
+    Lecture,
+    Exercise,
+}
+
+/// The `minutes` field of frontmatter: either a single value, or a value for
+/// each course in which the chapter appears.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Minutes {
+    All(u64),
+    PerCourse(BTreeMap<String, u64>),
+}
+
+// This is implemented by hand, rather than with `#[serde(untagged)]`, so that
+// errors point to the offending value.
+impl<'de> Deserialize<'de> for Minutes {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Minutes;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a number of minutes, or a map from course name to minutes"
+                )
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Minutes, E> {
+                Ok(Minutes::All(v))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> Result<Minutes, A::Error> {
+                Deserialize::deserialize(
+                    serde::de::value::MapAccessDeserializer::new(map),
+                )
+                .map(Minutes::PerCourse)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl Serialize for Minutes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Minutes::All(minutes) => serializer.serialize_u64(*minutes),
+            Minutes::PerCourse(minutes) => minutes.serialize(serializer),
+        }
+    }
+}
+
+impl Minutes {
+    /// Return the minutes for the given course, or None if this value does not
+    /// include that course.
+    pub fn for_course(&self, course: &str) -> Option<u64> {
+        match self {
+            Minutes::All(minutes) => Some(*minutes),
+            Minutes::PerCourse(minutes) => minutes.get(course).copied(),
+        }
+    }
+}
+
+/// An error in the frontmatter of a chapter, with its position in the
+/// original Markdown file.
+#[derive(Debug)]
+pub struct FrontmatterError {
+    pub source_path: Option<PathBuf>,
+    /// One-based line number within the Markdown file, if known.
+    pub line: Option<usize>,
+    /// One-based column number within the Markdown file, if known.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for FrontmatterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source_path {
+            Some(path) => write!(f, "{}", path.display())?,
+            None => write!(f, "<unknown>")?,
+        }
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for FrontmatterError {}
+
+/// A collection of frontmatter errors, reported together.
+#[derive(Debug)]
+pub struct FrontmatterErrors(pub Vec<FrontmatterError>);
+
+impl fmt::Display for FrontmatterErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} frontmatter error(s):", self.0.len())?;
+        for error in &self.0 {
+            writeln!(f, "  {error}")?;
+        }
+        Ok(())
+    }
 }
 
+impl std::error::Error for FrontmatterErrors {}
+
 /// Split a chapter's contents into frontmatter and the remaining contents.
+///
+/// Unknown fields and values of the wrong type are reported as a
+/// [`FrontmatterError`].
 pub fn split_frontmatter(
     chapter: &Chapter,
 ) -> anyhow::Result<(Frontmatter, String)> {
-    if let Some((frontmatter, content)) = matter(&chapter.content) {
-        let frontmatter: Frontmatter = serde_yaml::from_str(&frontmatter)
-            .with_context(|| {
-                format!("error parsing frontmatter in {:?}", chapter.source_path)
-            })?;
-
-        Ok((frontmatter, content))
-    } else {
-        Ok((Frontmatter::default(), chapter.content.clone()))
+    match parse_frontmatter(chapter) {
+        Ok(result) => Ok(result),
+        Err(mut errors) => Err(errors.remove(0).into()),
+    }
+}
+
+/// Check the frontmatter of every chapter in the book, returning all errors
+/// found rather than stopping at the first one.
+pub fn check_book(book: &Book) -> Result<(), FrontmatterErrors> {
+    FrontmatterCache::default().check_book(book)
+}
+
+/// Parsed frontmatter, which can be saved and reused by later builds of the
+/// book.
+///
+/// Entries are keyed by the SHA-256 digest of the chapter's content and of
+/// [`FIELDS`], so an edited chapter or a change to the fields simply misses the
+/// cache. Only valid frontmatter is cached, so errors are always reported.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct FrontmatterCache {
+    /// The format of the saved cache, which must be [`CACHE_VERSION`].
+    #[serde(default)]
+    version: u32,
+    entries: HashMap<String, CacheEntry>,
+    /// Keys of the entries for chapters in the current book, which are the
+    /// only ones saved.
+    #[serde(skip)]
+    used: HashSet<String>,
+}
+
+/// The format of saved caches. Change this whenever [`CacheEntry`] or the
+/// cache key changes, so that older caches are ignored.
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CacheEntry {
+    frontmatter: Frontmatter,
+    /// The range of the chapter's content following the frontmatter.
+    content: Range<usize>,
+}
+
+fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    for field in FIELDS {
+        hasher.update(field);
+        hasher.update([0]);
+    }
+    hasher.update([0]);
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+impl FrontmatterCache {
+    /// Load a cache saved with [`FrontmatterCache::save`]. A missing or
+    /// unreadable file, or one saved in another format, results in an empty
+    /// cache.
+    pub fn load(path: &Path) -> Self {
+        let Ok(json) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&json) {
+            Ok(cache) if cache.version == CACHE_VERSION => cache,
+            Ok(cache) => {
+                log::debug!(
+                    "Ignoring frontmatter cache {} in format {}",
+                    path.display(),
+                    cache.version
+                );
+                Self::default()
+            }
+            Err(e) => {
+                log::debug!("Ignoring frontmatter cache {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Save the entries for the chapters checked by
+    /// [`FrontmatterCache::check_book`], creating the parent directory if
+    /// necessary.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let entries: HashMap<_, _> = self
+            .entries
+            .iter()
+            .filter(|(key, _)| self.used.contains(*key))
+            .collect();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Creating {}", parent.display()))?;
+        }
+        let json = serde_json::to_string(
+            &serde_json::json!({ "version": CACHE_VERSION, "entries": entries }),
+        )?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Writing {}", path.display()))
+    }
+
+    /// Check the frontmatter of every chapter in the book, like
+    /// [`check_book`], caching the frontmatter of valid chapters.
+    pub fn check_book(&mut self, book: &Book) -> Result<(), FrontmatterErrors> {
+        let mut errors = Vec::new();
+        let (mut chapters, mut hits) = (0, 0);
+        for item in book.iter() {
+            let BookItem::Chapter(chapter) = item else {
+                continue;
+            };
+            chapters += 1;
+            let key = content_hash(&chapter.content);
+            self.used.insert(key.clone());
+            if self.entries.contains_key(&key) {
+                hits += 1;
+                continue;
+            }
+            match parse_frontmatter(chapter) {
+                // `matter` trims the content, which therefore ends with the
+                // last occurrence of the trimmed content.
+                Ok((frontmatter, content)) => {
+                    if let Some(start) = chapter.content.rfind(&content) {
+                        let content = start..start + content.len();
+                        self.entries
+                            .insert(key, CacheEntry { frontmatter, content });
+                    }
+                }
+                Err(chapter_errors) => errors.extend(chapter_errors),
+            }
+        }
+        log::debug!("Frontmatter cache hits: {hits} of {chapters} chapters");
+        if errors.is_empty() { Ok(()) } else { Err(FrontmatterErrors(errors)) }
+    }
+
+    /// Split a chapter's contents into frontmatter and the remaining contents,
+    /// like [`split_frontmatter`], using the cached frontmatter if possible.
+    pub fn split(&self, chapter: &Chapter) -> anyhow::Result<(Frontmatter, String)> {
+        let entry = self.entries.get(&content_hash(&chapter.content));
+        if let Some(entry) = entry
+            && let Some(content) = chapter.content.get(entry.content.clone())
+        {
+            return Ok((entry.frontmatter.clone(), content.to_string()));
+        }
+        split_frontmatter(chapter)
+    }
+}
+
+/// Find the position of the given top-level key in the YAML, as reported by
+/// the YAML parser, so that keys which are quoted or indented are found too.
+fn key_location(yaml: &str, key: &str) -> Option<serde_yaml::Location> {
+    // The error raised on reaching the key carries the key's position.
+    const FOUND: &str = "frontmatter key found";
+
+    struct Key<'a>(&'a str);
+
+    impl<'de> serde::de::DeserializeSeed<'de> for Key<'_> {
+        type Value = ();
+
+        fn deserialize<D: serde::Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<(), D::Error> {
+            deserializer.deserialize_str(self)
+        }
+    }
+
+    impl serde::de::Visitor<'_> for Key<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a field name")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<(), E> {
+            if v == self.0 { Err(E::custom(FOUND)) } else { Ok(()) }
+        }
+    }
+
+    struct Keys<'a>(&'a str);
+
+    impl<'de> serde::de::Visitor<'de> for Keys<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a mapping of fields to values")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<(), A::Error> {
+            while map.next_key_seed(Key(self.0))?.is_some() {
+                map.next_value::<serde::de::IgnoredAny>()?;
+            }
+            Ok(())
+        }
+    }
+
+    let deserializer = serde_yaml::Deserializer::from_str(yaml);
+    let error =
+        serde::Deserializer::deserialize_map(deserializer, Keys(key)).err()?;
+    if error.to_string().contains(FOUND) { error.location() } else { None }
+}
+
+/// Parse and validate the frontmatter of a chapter, returning all errors in
+/// that frontmatter.
+fn parse_frontmatter(
+    chapter: &Chapter,
+) -> Result<(Frontmatter, String), Vec<FrontmatterError>> {
+    let Some((frontmatter, content)) = matter(&chapter.content) else {
+        return Ok((Frontmatter::default(), chapter.content.clone()));
+    };
+    // `matter` trims the frontmatter, so find where it begins in the file in
+    // order to translate positions.
+    let start = chapter.content.find(&frontmatter).unwrap_or(0);
+    let first_line = chapter.content[..start].matches('\n').count() + 1;
+    let error = |line: Option<usize>, column: Option<usize>, message: String| {
+        FrontmatterError {
+            source_path: chapter.source_path.clone(),
+            line: line.map(|l| l + first_line - 1),
+            column,
+            message,
+        }
+    };
+
+    // Check for unknown keys first, so that they can all be reported with
+    // suggestions.
+    let mapping: serde_yaml::Mapping = match serde_yaml::from_str(&frontmatter) {
+        Ok(serde_yaml::Value::Mapping(mapping)) => mapping,
+        Ok(serde_yaml::Value::Null) => serde_yaml::Mapping::new(),
+        Ok(_) => {
+            return Err(vec![error(
+                Some(1),
+                Some(1),
+                "frontmatter must be a mapping of fields to values".into(),
+            )]);
+        }
+        Err(e) => {
+            let location = e.location();
+            return Err(vec![error(
+                location.as_ref().map(|l| l.line()),
+                location.as_ref().map(|l| l.column()),
+                format!("invalid YAML: {e}"),
+            )]);
+        }
+    };
+    let mut errors = Vec::new();
+    for key in mapping.keys() {
+        let Some(key) = key.as_str() else {
+            errors.push(error(None, None, format!("invalid key {key:?}")));
+            continue;
+        };
+        if FIELDS.contains(&key) {
+            continue;
+        }
+        let mut message = format!("unknown field `{key}`");
+        if let Some(suggestion) = suggest(key, FIELDS.iter().copied()) {
+            message.push_str(&format!(", did you mean `{suggestion}`?"));
+        }
+        let location = key_location(&frontmatter, key);
+        errors.push(error(
+            location.as_ref().map(|l| l.line()),
+            location.as_ref().map(|l| l.column()),
+            message,
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    match serde_yaml::from_str(&frontmatter) {
+        Ok(frontmatter) => Ok((frontmatter, content)),
+        Err(e) => {
+            let location = e.location();
+            Err(vec![error(
+                location.as_ref().map(|l| l.line()),
+                location.as_ref().map(|l| l.column()),
+                e.to_string(),
+            )])
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chapter(content: &str) -> Chapter {
+        Chapter::new("Test", content.into(), "test.md", Vec::new())
+    }
+
+    #[test]
+    fn valid() {
+        let (frontmatter, content) =
+            split_frontmatter(&chapter("---\nminutes: 5\n---\n# Hello")).unwrap();
+        assert_eq!(frontmatter.minutes, Some(Minutes::All(5)));
+        assert_eq!(content, "# Hello");
+    }
+
+    #[test]
+    fn kind() {
+        let (frontmatter, _) =
+            split_frontmatter(&chapter("---\nkind: exercise\n---\n")).unwrap();
+        assert_eq!(frontmatter.kind, Some(SlideKind::Exercise));
+        assert!(split_frontmatter(&chapter("---\nkind: lab\n---\n")).is_err());
+    }
+
+    #[test]
+    fn per_course_minutes() {
+        let (frontmatter, _) = split_frontmatter(&chapter(
+            "---\nminutes: {Fundamentals: 10, Short: 5}\n---\n",
+        ))
+        .unwrap();
+        let minutes = frontmatter.minutes.unwrap();
+        assert_eq!(minutes.for_course("Short"), Some(5));
+        assert_eq!(minutes.for_course("Other"), None);
+    }
+
+    #[test]
+    fn no_frontmatter() {
+        let (frontmatter, content) = split_frontmatter(&chapter("# Hello")).unwrap();
+        assert_eq!(frontmatter.minutes, None);
+        assert_eq!(content, "# Hello");
+    }
+
+    #[test]
+    fn unknown_field_suggestion() {
+        let errors =
+            parse_frontmatter(&chapter("---\nminutes: 5\nsesion: S\nfoo: 1\n---\n"))
+                .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "test.md:3:1: unknown field `sesion`, did you mean `session`?"
+        );
+        assert_eq!(errors[1].to_string(), "test.md:4:1: unknown field `foo`");
+    }
+
+    #[test]
+    fn unknown_field_quoted_or_indented() {
+        let errors =
+            parse_frontmatter(&chapter("---\nminutes: 5\n\"sesion\": S\n---\n"))
+                .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "test.md:3:1: unknown field `sesion`, did you mean `session`?"
+        );
+        let errors =
+            parse_frontmatter(&chapter("---\n{ minutes: 5,\n  foo: 1 }\n---\n"))
+                .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "test.md:3:3: unknown field `foo`");
+    }
+
+    #[test]
+    fn invalid_type_position() {
+        let errors =
+            parse_frontmatter(&chapter("\n---\ncourse: C\nminutes: five\n---\n"))
+                .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(4));
+        assert!(errors[0].message.contains("minutes"), "{}", errors[0].message);
+    }
+
+    #[test]
+    fn check_book_collects_errors() {
+        let mut book = Book::new();
+        book.push_item(chapter("---\nminuts: 5\n---\n"));
+        book.push_item(chapter("---\nminutes: 5\n---\n"));
+        book.push_item(chapter("---\ncourse: [\n---\n"));
+        let errors = check_book(&book).unwrap_err();
+        assert_eq!(errors.0.len(), 2);
+    }
+
+    #[test]
+    fn cache() {
+        let content = "---\nminutes: {A: 5}\n---\n\n# Hello\n";
+        let mut book = Book::new();
+        book.push_item(chapter(content));
+        book.push_item(chapter("# No frontmatter\n"));
+        let mut cache = FrontmatterCache::default();
+        cache.check_book(&book).unwrap();
+
+        let dir = std::env::temp_dir()
+            .join(format!("mdbook-course-cache-{}", std::process::id()));
+        let path = dir.join("frontmatter.json");
+        cache.save(&path).unwrap();
+        let cache = FrontmatterCache::load(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(cache.entries.len(), 2);
+
+        let (frontmatter, content) = cache.split(&chapter(content)).unwrap();
+        assert_eq!(frontmatter.minutes.unwrap().for_course("A"), Some(5));
+        assert_eq!(content, "# Hello");
+        assert!(FrontmatterCache::load(&dir).entries.is_empty());
+    }
+
+    #[test]
+    fn cache_version() {
+        let dir = std::env::temp_dir()
+            .join(format!("mdbook-course-cache-version-{}", std::process::id()));
+        let path = dir.join("frontmatter.json");
+        let mut book = Book::new();
+        book.push_item(chapter("---\nminutes: 5\n---\n"));
+        let mut cache = FrontmatterCache::default();
+        cache.check_book(&book).unwrap();
+        cache.save(&path).unwrap();
+        assert_eq!(FrontmatterCache::load(&path).entries.len(), 1);
+
+        let json = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace(&format!("\"version\":{CACHE_VERSION}"), "\"version\":0");
+        std::fs::write(&path, json).unwrap();
+        let cache = FrontmatterCache::load(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(cache.entries.is_empty());
     }
 }
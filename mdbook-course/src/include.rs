@@ -0,0 +1,163 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of mdbook's `{{#include}}` links, for tools which process the
+//! course content outside of mdbook.
+//!
+//! This follows mdbook's rules for line ranges and anchors, except that a
+//! missing file or anchor is an error.
+
+use anyhow::Context;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+lazy_static! {
+    static ref INCLUDE: Regex =
+        Regex::new(r"\\\{\{#[^}]*\}\}|\{\{\s*#include\s+([^}]+)\}\}").unwrap();
+    static ref ANCHOR_START: Regex = Regex::new(r"ANCHOR:\s*([\w_-]+)").unwrap();
+    static ref ANCHOR_END: Regex = Regex::new(r"ANCHOR_END:\s*([\w_-]+)").unwrap();
+}
+
+/// Includes nested more deeply than this are left unresolved, as in mdbook.
+const MAX_DEPTH: usize = 10;
+
+/// Replace each `{{#include}}` link in the content with the lines it refers
+/// to. Paths are relative to `dir`, the directory containing the content.
+pub fn resolve_includes(content: &str, dir: &Path) -> anyhow::Result<String> {
+    resolve(content, dir, 0)
+}
+
+fn resolve(content: &str, dir: &Path, depth: usize) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for captures in INCLUDE.captures_iter(content) {
+        let whole = captures.get(0).unwrap();
+        out.push_str(&content[last..whole.start()]);
+        last = whole.end();
+        let Some(link) = captures.get(1) else {
+            // An escaped link, `\{{#...}}`, is replaced by the link itself.
+            out.push_str(&whole.as_str()[1..]);
+            continue;
+        };
+        if depth >= MAX_DEPTH {
+            out.push_str(whole.as_str());
+            continue;
+        }
+
+        let (path, selector) = match link.as_str().trim().split_once(':') {
+            Some((path, selector)) => (path, Some(selector)),
+            None => (link.as_str().trim(), None),
+        };
+        let path = dir.join(path);
+        let included = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read {}", path.display()))?;
+        let lines = select(&included, selector)
+            .with_context(|| format!("Unable to include {}", path.display()))?;
+        let parent = path.parent().unwrap_or(dir);
+        out.push_str(&resolve(&lines, parent, depth + 1)?);
+    }
+    out.push_str(&content[last..]);
+    Ok(out)
+}
+
+/// Select the lines given by the part of an include link after the path: a
+/// line range such as `2:10`, `2:` or `:10`, a single line, or an anchor name.
+fn select(content: &str, selector: Option<&str>) -> anyhow::Result<String> {
+    let Some(selector) = selector else {
+        return Ok(content.lines().collect::<Vec<_>>().join("\n"));
+    };
+    let mut parts = selector.splitn(2, ':');
+    let first = parts.next().unwrap_or("");
+    let start = match first {
+        "" => 0,
+        _ => match first.parse::<usize>() {
+            Ok(line) => line.saturating_sub(1),
+            Err(_) => return anchored_lines(content, first),
+        },
+    };
+    let end = match parts.next() {
+        // A single line.
+        None if !first.is_empty() => start + 1,
+        None => usize::MAX,
+        Some(end) => end.parse().unwrap_or(usize::MAX),
+    };
+    Ok(content
+        .lines()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Select the lines between `ANCHOR: name` and `ANCHOR_END: name`, omitting
+/// any other anchor lines.
+fn anchored_lines(content: &str, anchor: &str) -> anyhow::Result<String> {
+    let mut lines = content.lines();
+    if !lines
+        .any(|line| ANCHOR_START.captures(line).is_some_and(|c| &c[1] == anchor))
+    {
+        anyhow::bail!("anchor {anchor:?} not found");
+    }
+    let mut retained = Vec::new();
+    for line in lines {
+        if ANCHOR_END.captures(line).is_some_and(|c| &c[1] == anchor) {
+            break;
+        }
+        if !ANCHOR_START.is_match(line) && !ANCHOR_END.is_match(line) {
+            retained.push(line);
+        }
+    }
+    Ok(retained.join("\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILE: &str = "one\n// ANCHOR: a\ntwo\n// ANCHOR: b\nthree\n// ANCHOR_END: b\n\
+                        // ANCHOR_END: a\nfour";
+
+    #[test]
+    fn selectors() {
+        assert_eq!(select("a\nb\n", None).unwrap(), "a\nb");
+        assert_eq!(select(FILE, Some("3")).unwrap(), "two");
+        assert_eq!(select(FILE, Some("2:3")).unwrap(), "// ANCHOR: a\ntwo");
+        assert_eq!(select(FILE, Some(":1")).unwrap(), "one");
+        assert_eq!(select(FILE, Some("8:")).unwrap(), "four");
+        assert_eq!(select(FILE, Some("a")).unwrap(), "two\nthree");
+        assert_eq!(select(FILE, Some("b")).unwrap(), "three");
+        assert!(select(FILE, Some("c")).is_err());
+    }
+
+    #[test]
+    fn resolve_files() {
+        let dir = std::env::temp_dir().join("mdbook-course-include-test");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/code.rs"), FILE).unwrap();
+        fs::write(dir.join("sub/text.md"), "```rust\n{{#include code.rs:b}}\n```")
+            .unwrap();
+
+        assert_eq!(
+            resolve_includes(
+                "A\n{{#include sub/text.md}}\n\\{{#include x}}\n{{ #include sub/code.rs:1 }}",
+                &dir
+            )
+            .unwrap(),
+            "A\n```rust\nthree\n```\n{{#include x}}\none"
+        );
+        assert!(resolve_includes("{{#include missing.md}}", &dir).is_err());
+    }
+}
@@ -12,8 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod agenda;
+pub mod chart;
+pub mod concepts;
+pub mod config;
 pub mod course;
+pub mod csv;
+pub mod diff;
+pub mod export;
 pub mod frontmatter;
+pub mod include;
+pub mod lint;
 pub mod markdown;
+pub mod pacing;
+pub mod profile;
+pub mod quiz;
 pub mod replacements;
+pub mod run_sheet;
+#[cfg(test)]
+mod test_util;
 pub mod timing_info;
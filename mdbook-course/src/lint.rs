@@ -0,0 +1,597 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks for structural and timing mistakes in the course hierarchy.
+//!
+//! Each check is a [`Rule`], identified by a kebab-case name. Violations can
+//! be allowed for specific source paths in the `[preprocessor.course.lint]`
+//! section of `book.toml`:
+//!
+//! ```toml
+//! [preprocessor.course.lint.allow]
+//! untimed-slide-with-notes = ["welcome-day-1.md"]
+//! ```
+
+use crate::concepts::{self, Problem};
+use crate::config::LintConfig;
+use crate::course::{Courses, Session};
+use crate::markdown::{speaker_notes, suggest};
+use mdbook::book::{Book, BookItem};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single violation of a lint rule.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub source_path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {}: {}",
+            self.severity,
+            self.rule,
+            self.source_path.display(),
+            self.message
+        )
+    }
+}
+
+/// The information available to lint rules.
+pub struct Context<'a> {
+    pub courses: &'a Courses,
+    /// Content of each chapter (without frontmatter), keyed by source path.
+    pub content: HashMap<&'a Path, &'a str>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(courses: &'a Courses, book: &'a Book) -> Self {
+        let mut content = HashMap::new();
+        for item in book.iter() {
+            if let BookItem::Chapter(chapter) = item
+                && let Some(source_path) = &chapter.source_path
+            {
+                content.insert(source_path.as_path(), chapter.content.as_str());
+            }
+        }
+        Context { courses, content }
+    }
+}
+
+/// A lint rule, checking one invariant of the course structure.
+pub trait Rule {
+    /// The name of the rule, as used in `book.toml`.
+    fn name(&self) -> &'static str;
+
+    fn severity(&self) -> Severity;
+
+    /// Check the rule, returning the source path and a message for each
+    /// violation.
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)>;
+}
+
+/// All lint rules, in the order they are checked.
+pub const RULES: &[&dyn Rule] = &[
+    &UntimedSession,
+    &UntimedSlideWithNotes,
+    &MisplacedTargetMinutes,
+    &DuplicateSessionName,
+    &SubSlideMinutes,
+    &QuizMinutes,
+    &ConceptOrder,
+    &UnprovidedConcept,
+];
+
+/// Check all rules, returning the violations which are not allowed by the
+/// configuration.
+///
+/// Allowing an unknown rule is an error, so that a misspelled rule name does
+/// not go unnoticed.
+pub fn lint(
+    context: &Context,
+    config: &LintConfig,
+) -> anyhow::Result<Vec<Diagnostic>> {
+    for name in config.allow.keys() {
+        if RULES.iter().any(|rule| rule.name() == name) {
+            continue;
+        }
+        let mut message = format!("unknown lint rule `{name}` in lint.allow");
+        if let Some(suggestion) = suggest(name, RULES.iter().map(|rule| rule.name()))
+        {
+            message.push_str(&format!(", did you mean `{suggestion}`?"));
+        }
+        anyhow::bail!(message);
+    }
+    let mut diagnostics = Vec::new();
+    for rule in RULES {
+        for (source_path, message) in rule.check(context) {
+            if config.is_allowed(rule.name(), &source_path) {
+                continue;
+            }
+            diagnostics.push(Diagnostic {
+                rule: rule.name(),
+                severity: rule.severity(),
+                source_path,
+                message,
+            });
+        }
+    }
+    Ok(diagnostics)
+}
+
+/// The source path of the first chapter in a session, used to identify the
+/// session in diagnostics.
+fn session_path(session: &Session) -> PathBuf {
+    session
+        .into_iter()
+        .flat_map(|segment| segment.into_iter())
+        .flat_map(|slide| slide.source_paths.first())
+        .next()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// A session must contain at least one segment that takes some time.
+struct UntimedSession;
+
+impl Rule for UntimedSession {
+    fn name(&self) -> &'static str {
+        "untimed-session"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        for course in context.courses {
+            for session in course {
+                if session.minutes() == 0 {
+                    violations.push((
+                        session_path(session),
+                        format!(
+                            "session {:?} in course {:?} has no timed segments",
+                            session.name, course.name
+                        ),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// A slide with speaker notes is presumably taught, so should take some time.
+struct UntimedSlideWithNotes;
+
+impl Rule for UntimedSlideWithNotes {
+    fn name(&self) -> &'static str {
+        "untimed-slide-with-notes"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        for course in context.courses {
+            // Sessions without any timing are reported by `UntimedSession`.
+            for session in course.into_iter().filter(|s| s.minutes() > 0) {
+                for segment in session {
+                    for slide in segment {
+                        let Some(source_path) = slide.source_paths.first() else {
+                            continue;
+                        };
+                        let has_notes = slide.source_paths.iter().any(|path| {
+                            context.content.get(path.as_path()).is_some_and(
+                                |content| speaker_notes(content).is_some(),
+                            )
+                        });
+                        if slide.minutes() == 0 && has_notes {
+                            violations.push((
+                                source_path.clone(),
+                                format!(
+                                    "slide {:?} has speaker notes but takes 0 minutes",
+                                    slide.name
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Only the first segment in a session may declare the session's target
+/// duration.
+struct MisplacedTargetMinutes;
+
+impl Rule for MisplacedTargetMinutes {
+    fn name(&self) -> &'static str {
+        "misplaced-target-minutes"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        for course in context.courses {
+            for session in course {
+                for segment in session.into_iter().skip(1) {
+                    if segment.declared_target_minutes().is_none() {
+                        continue;
+                    }
+                    let source_path = segment
+                        .into_iter()
+                        .flat_map(|slide| slide.source_paths.first())
+                        .next()
+                        .cloned()
+                        .unwrap_or_default();
+                    violations.push((
+                        source_path,
+                        format!(
+                            "segment {:?} declares target_minutes, but is not the \
+                             first segment in session {:?}",
+                            segment.name, session.name
+                        ),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Session names must be unique across all courses.
+struct DuplicateSessionName;
+
+impl Rule for DuplicateSessionName {
+    fn name(&self) -> &'static str {
+        "duplicate-session-name"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for course in context.courses {
+            for session in course {
+                if let Some(other_course) = seen.get(session.name.as_str()) {
+                    violations.push((
+                        session_path(session),
+                        format!(
+                            "session {:?} in course {:?} has the same name as a \
+                             session in course {:?}",
+                            session.name, course.name, other_course
+                        ),
+                    ));
+                } else {
+                    seen.insert(&session.name, &course.name);
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Timing is given for a slide as a whole, so sub-slides should not declare
+/// `minutes`.
+struct SubSlideMinutes;
+
+impl Rule for SubSlideMinutes {
+    fn name(&self) -> &'static str {
+        "sub-slide-minutes"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        for course in context.courses {
+            for session in course {
+                for segment in session {
+                    for slide in segment {
+                        for (source_path, minutes) in slide.chapter_minutes().skip(1)
+                        {
+                            if minutes > 0 {
+                                violations.push((
+                                    source_path.to_owned(),
+                                    format!(
+                                        "sub-slide of {:?} declares {minutes} minutes",
+                                        slide.name
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// The quizzes in a slide are part of its time, so they should not take longer
+/// than the slide's `minutes`.
+struct QuizMinutes;
+
+impl Rule for QuizMinutes {
+    fn name(&self) -> &'static str {
+        "quiz-minutes"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        for course in context.courses {
+            for session in course {
+                for segment in session {
+                    for slide in segment {
+                        let quiz_minutes: u64 =
+                            slide.quizzes().iter().map(|q| q.minutes).sum();
+                        if quiz_minutes > slide.minutes() {
+                            violations.push((
+                                slide.source_path().map(Path::to_owned).unwrap_or_default(),
+                                format!(
+                                    "quizzes in slide {:?} take {quiz_minutes} minutes, \
+                                     but the slide only takes {} minutes",
+                                    slide.name,
+                                    slide.minutes()
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// A concept required by a slide should be provided by an earlier slide, not a
+/// later one.
+struct ConceptOrder;
+
+impl Rule for ConceptOrder {
+    fn name(&self) -> &'static str {
+        "concept-order"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        for course in context.courses {
+            for problem in concepts::check(course) {
+                if let Problem::ProvidedLater { slide, concept, provider } = problem
+                {
+                    violations.push((
+                        slide.source_path().map(Path::to_owned).unwrap_or_default(),
+                        format!(
+                            "slide {:?} requires {concept:?}, which is only provided \
+                             later in course {:?}, by slide {:?}",
+                            slide.name, course.name, provider.name
+                        ),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// A concept required by a slide should be provided by some slide in the
+/// course.
+struct UnprovidedConcept;
+
+impl Rule for UnprovidedConcept {
+    fn name(&self) -> &'static str {
+        "unprovided-concept"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, context: &Context) -> Vec<(PathBuf, String)> {
+        let mut violations = Vec::new();
+        for course in context.courses {
+            for problem in concepts::check(course) {
+                if let Problem::NotProvided { slide, concept } = problem {
+                    violations.push((
+                        slide.source_path().map(Path::to_owned).unwrap_or_default(),
+                        format!(
+                            "slide {:?} requires {concept:?}, which no slide in \
+                             course {:?} provides",
+                            slide.name, course.name
+                        ),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{chapter, extract};
+
+    fn lint_book(book: Book, config: &LintConfig) -> Vec<String> {
+        let (courses, book) = extract(book);
+        let context = Context::new(&courses, &book);
+        lint(&context, config).unwrap().iter().map(|d| d.to_string()).collect()
+    }
+
+    fn test_book() -> Book {
+        let mut book = Book::new();
+        book.push_item(chapter(
+            "a",
+            "---\ncourse: A\nsession: S\nminutes: 5\n---\n",
+            vec![
+                chapter(
+                    "a1",
+                    "---\nminutes: 0\n---\n<details>notes</details>",
+                    vec![chapter("a1x", "---\nminutes: 2\n---\n", Vec::new())],
+                ),
+                chapter(
+                    "a2",
+                    "---\nrequires: [y]\n---\n<details>notes</details>",
+                    Vec::new(),
+                ),
+            ],
+        ));
+        book.push_item(chapter(
+            "b",
+            "---\ntarget_minutes: 10\nminutes: 5\nrequires: [x]\nprovides: [y]\n---\n",
+            Vec::new(),
+        ));
+        book.push_item(chapter(
+            "c",
+            "---\ncourse: B\nsession: S\n---\n",
+            Vec::new(),
+        ));
+        book
+    }
+
+    #[test]
+    fn all_rules() {
+        assert_eq!(
+            lint_book(test_book(), &LintConfig::default()),
+            vec![
+                "error[untimed-session]: c.md: session \"S\" in course \"B\" has no \
+                 timed segments",
+                "warning[untimed-slide-with-notes]: a2.md: slide \"a2\" has speaker \
+                 notes but takes 0 minutes",
+                "error[misplaced-target-minutes]: b.md: segment \"b\" declares \
+                 target_minutes, but is not the first segment in session \"S\"",
+                "error[duplicate-session-name]: c.md: session \"S\" in course \"B\" \
+                 has the same name as a session in course \"A\"",
+                "warning[sub-slide-minutes]: a1x.md: sub-slide of \"a1\" declares 2 \
+                 minutes",
+                "error[concept-order]: a2.md: slide \"a2\" requires \"y\", which is \
+                 only provided later in course \"A\", by slide \"b\"",
+                "warning[unprovided-concept]: b.md: slide \"b\" requires \"x\", which \
+                 no slide in course \"A\" provides",
+            ]
+        );
+    }
+
+    #[test]
+    fn allowed() {
+        let mut config = LintConfig::default();
+        config.allow.insert("untimed-session".into(), vec!["*".into()]);
+        config.allow.insert("duplicate-session-name".into(), vec!["c.md".into()]);
+        config.allow.insert("sub-slide-minutes".into(), vec!["other.md".into()]);
+        assert_eq!(lint_book(test_book(), &config).len(), 5);
+    }
+
+    #[test]
+    fn notes_in_sub_chapters_only() {
+        let mut book = Book::new();
+        book.push_item(chapter(
+            "a",
+            "---\ncourse: A\nsession: S\nminutes: 5\n---\n",
+            vec![
+                chapter(
+                    "code",
+                    "```html\n<details>\n```\n\nInline <details> text.\n",
+                    Vec::new(),
+                ),
+                chapter(
+                    "parent",
+                    "",
+                    vec![chapter("child", "<details>notes</details>\n", Vec::new())],
+                ),
+            ],
+        ));
+        assert_eq!(
+            lint_book(book, &LintConfig::default()),
+            vec![
+                "warning[untimed-slide-with-notes]: parent.md: slide \"parent\" has \
+                 speaker notes but takes 0 minutes"
+            ]
+        );
+    }
+
+    #[test]
+    fn quiz_minutes() {
+        let quiz =
+            "```quiz\nquestion: Q?\noptions: [a, b]\nanswer: a\nminutes: 3\n```\n";
+        let mut book = Book::new();
+        book.push_item(chapter(
+            "a",
+            &format!("---\ncourse: A\nsession: S\nminutes: 5\n---\n{quiz}"),
+            vec![chapter("b", &format!("---\nminutes: 2\n---\n{quiz}"), Vec::new())],
+        ));
+        assert_eq!(
+            lint_book(book, &LintConfig::default()),
+            vec![
+                "error[quiz-minutes]: b.md: quizzes in slide \"b\" take 3 minutes, but \
+                 the slide only takes 2 minutes"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_allowed_rule() {
+        let (courses, book) = extract(test_book());
+        let context = Context::new(&courses, &book);
+        let mut config = LintConfig::default();
+        config.allow.insert("untimed-sesion".into(), vec!["*".into()]);
+        assert_eq!(
+            lint(&context, &config).unwrap_err().to_string(),
+            "unknown lint rule `untimed-sesion` in lint.allow, did you mean \
+             `untimed-session`?"
+        );
+    }
+}
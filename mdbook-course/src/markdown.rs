@@ -12,9 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use lazy_static::lazy_static;
+use pulldown_cmark::{Event, Parser};
+use regex::Regex;
+use serde::Deserialize;
 use std::fmt;
+use std::ops::Range;
 use std::path::Path;
 
+lazy_static! {
+    static ref DETAILS_TAG: Regex = Regex::new(r"(?i)<(/?)details\b[^>]*>").unwrap();
+}
+
 /// Given a source_path for the markdown file being rendered and a source_path
 /// for the target, generate a relative link.
 pub fn relative_link(
@@ -25,63 +34,240 @@ pub fn relative_link(
     let target_path = target_path.as_ref();
 
     let mut dotdot = -1;
+    let mut relative = target_path;
     for parent in doc_path.ancestors() {
-        if target_path.starts_with(parent) {
+        if let Ok(path) = target_path.strip_prefix(parent) {
+            relative = path;
             break;
         }
         dotdot += 1;
     }
     if dotdot > 0 {
-        format!("{}{}", "../".repeat(dotdot as usize), target_path.display())
+        format!("{}{}", "../".repeat(dotdot as usize), relative.display())
     } else {
-        format!("./{}", target_path.display())
+        format!("./{}", relative.display())
     }
 }
 
-/// Represent the given duration in a human-readable way.
+/// How generated outlines refer to segments and slides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Links<'a> {
+    /// Plain text names, for renderers which cannot follow links between
+    /// chapters, such as the pandoc PDF output.
+    Plain,
+    /// Links relative to the chapter with the given source path.
+    RelativeTo(&'a Path),
+}
+
+impl Links<'_> {
+    /// Format a reference to the chapter with the given source path, if any.
+    pub fn link(&self, text: &str, target: Option<&Path>) -> String {
+        match (self, target) {
+            (Links::RelativeTo(doc_path), Some(target)) => {
+                let text = text.replace('[', "\\[").replace(']', "\\]");
+                format!("[{text}]({})", relative_link(doc_path, target))
+            }
+            _ => text.to_string(),
+        }
+    }
+
+    /// Format a table cell linking to the chapter with the given source path,
+    /// if any, for [`Table::add_linked_row`].
+    pub fn cell(&self, target: Option<&Path>) -> Option<String> {
+        match (self, target) {
+            (Links::RelativeTo(doc_path), Some(target)) => {
+                Some(format!("[→]({})", relative_link(doc_path, target)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A top-level `<details>` block in Markdown content.
+struct DetailsBlock {
+    /// The whole block, including its tags.
+    outer: Range<usize>,
+    /// The content of the block, between the opening and closing tags.
+    inner: Range<usize>,
+}
+
+/// Find the top-level `<details>` blocks in the given Markdown content. A block
+/// which is not closed extends to the end of the content.
 ///
-/// This will round times longer than 5 minutes to the next 5-minute interval.
-pub fn duration(mut minutes: u64) -> String {
-    if minutes > 5 {
-        minutes += 4;
-        minutes -= minutes % 5;
+/// Only HTML blocks are considered, so `<details>` in code blocks or inline
+/// HTML is ignored.
+fn details_blocks(content: &str) -> Vec<DetailsBlock> {
+    let mut depth = 0usize;
+    let mut blocks: Vec<DetailsBlock> = Vec::new();
+    for (event, range) in Parser::new(content).into_offset_iter() {
+        let Event::Html(_) = event else {
+            continue;
+        };
+        for tag in DETAILS_TAG.captures_iter(&content[range.clone()]) {
+            let whole = tag.get(0).unwrap();
+            let start = range.start + whole.start();
+            let end = range.start + whole.end();
+            if tag[1].is_empty() {
+                if depth == 0 {
+                    blocks.push(DetailsBlock {
+                        outer: start..content.len(),
+                        inner: end..content.len(),
+                    });
+                }
+                depth += 1;
+            } else if depth > 0 {
+                depth -= 1;
+                if depth == 0
+                    && let Some(block) = blocks.last_mut()
+                {
+                    block.outer.end = end;
+                    block.inner.end = start;
+                }
+            }
+        }
     }
+    blocks
+}
 
+/// Find the speaker notes in the given Markdown content, which are the last
+/// top-level `<details>` block. The returned range covers the content of the
+/// block, between the opening and closing tags.
+///
+/// Only HTML blocks are considered, so `<details>` in code blocks or inline
+/// HTML is ignored.
+pub fn speaker_notes(content: &str) -> Option<Range<usize>> {
+    details_blocks(content).pop().map(|block| block.inner)
+}
+
+/// Remove the speaker notes found by [`speaker_notes`], including their tags,
+/// from the given Markdown content. Other `<details>` blocks are left in place.
+pub fn strip_speaker_notes(content: &str) -> String {
+    let mut out = match details_blocks(content).pop() {
+        Some(notes) => {
+            content[..notes.outer.start].to_string() + &content[notes.outer.end..]
+        }
+        None => content.to_string(),
+    };
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// Represent the given duration in a human-readable way.
+///
+/// The text is in English and is translated by the `.po` catalogs, which have
+/// a message for each duration that appears in the book.
+///
+/// This will round the duration with [`round_minutes`].
+pub fn duration(minutes: u64, rounding: u64) -> String {
+    let minutes = round_minutes(minutes, rounding);
     let (hours, minutes) = (minutes / 60, minutes % 60);
     match (hours, minutes) {
-        (0, 1) => "1 minute".into(),
-        (0, m) => format!("{m} minutes"),
-        (1, 0) => "1 hour".into(),
-        (1, m) => format!("1 hour and {m} minutes"),
-        (h, 0) => format!("{h} hours"),
-        (h, m) => format!("{h} hours and {m} minutes"),
+        (0, m) => count(m, "minute"),
+        (h, 0) => count(h, "hour"),
+        (h, m) => format!("{} and {}", count(h, "hour"), count(m, "minute")),
     }
 }
 
+/// Round times longer than `rounding` minutes up to the next multiple of it, as
+/// configured with `timing.rounding`. A `rounding` of 1 disables rounding.
+pub fn round_minutes(minutes: u64, rounding: u64) -> u64 {
+    let rounding = rounding.max(1);
+    if minutes > rounding { minutes.div_ceil(rounding) * rounding } else { minutes }
+}
+
+/// Format a count of some unit, such as "1 minute" or "5 minutes".
+pub fn count(n: u64, unit: &str) -> String {
+    if n == 1 { format!("1 {unit}") } else { format!("{n} {unit}s") }
+}
+
+/// Convert a name to lower-case words separated by dashes, for use in file
+/// names.
+pub fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Suggest the candidate most similar to the given, unknown, name, if any is
+/// close enough to be a likely misspelling of it.
+pub fn suggest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|candidate| (strsim::levenshtein(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 3)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// The style in which [`Table`] formats tables.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    /// Compact GitHub-flavored Markdown tables.
+    #[default]
+    Gfm,
+    /// Pipe tables with aligned columns, for pandoc. Pandoc sizes the columns
+    /// of wide tables by the length of the separator line, so this is as wide
+    /// as the contents of each column.
+    Pandoc,
+}
+
 /// Table implements Display to format a two-dimensional table as markdown,
-/// following https://github.github.com/gfm/#tables-extension-.
+/// following https://github.github.com/gfm/#tables-extension-, in the
+/// [`TableStyle::Gfm`] style unless another is given with
+/// [`Table::with_style`].
 pub struct Table<const N: usize> {
     header: [String; N],
-    rows: Vec<[String; N]>,
+    /// The rows, each with the cell linking to it, if any.
+    rows: Vec<([String; N], Option<String>)>,
+    style: TableStyle,
 }
 
 impl<const N: usize> Table<N> {
     pub fn new(header: [String; N]) -> Self {
-        Self { header, rows: Vec::new() }
+        Self { header, rows: Vec::new(), style: TableStyle::default() }
+    }
+
+    /// Format this table in the given style, such as the renderer's
+    /// [`RendererOptions::tables`](crate::config::RendererOptions::tables).
+    pub fn with_style(mut self, style: TableStyle) -> Self {
+        self.style = style;
+        self
     }
 
     pub fn add_row(&mut self, row: [String; N]) {
-        self.rows.push(row);
+        self.rows.push((row, None));
+    }
+
+    /// Add a row with a cell linking to it, from [`Links::cell`].
+    ///
+    /// The links are in a column of their own, after the others, so that the
+    /// other cells are the same whether or not the table is linked. The `.po`
+    /// catalogs then translate them the same way in every renderer.
+    pub fn add_linked_row(&mut self, row: [String; N], link: Option<String>) {
+        self.rows.push((row, link));
     }
 
     fn write_row<'a, I: Iterator<Item = &'a str>>(
         &self,
         f: &mut fmt::Formatter<'_>,
         iter: I,
+        widths: &[usize],
     ) -> fmt::Result {
         write!(f, "|")?;
-        for cell in iter {
-            write!(f, " {} |", cell)?;
+        for (cell, width) in iter.zip(widths) {
+            let padding = width.saturating_sub(cell.chars().count());
+            write!(f, " {cell}{:padding$} |", "")?;
         }
         writeln!(f)
     }
@@ -89,10 +275,47 @@ impl<const N: usize> Table<N> {
 
 impl<const N: usize> fmt::Display for Table<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.write_row(f, self.header.iter().map(|s| s.as_str()))?;
-        self.write_row(f, self.header.iter().map(|_| "-"))?;
-        for row in &self.rows {
-            self.write_row(f, row.iter().map(|s| s.as_str()))?;
+        let linked = self.rows.iter().any(|(_, link)| link.is_some());
+        let header: Vec<&str> = self
+            .header
+            .iter()
+            .map(String::as_str)
+            .chain(linked.then_some(""))
+            .collect();
+        let rows: Vec<Vec<&str>> = self
+            .rows
+            .iter()
+            .map(|(row, link)| {
+                row.iter()
+                    .map(String::as_str)
+                    .chain(linked.then(|| link.as_deref().unwrap_or_default()))
+                    .collect()
+            })
+            .collect();
+        let mut widths = vec![0; header.len()];
+        if self.style == TableStyle::Pandoc {
+            for row in std::iter::once(&header).chain(&rows) {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+        }
+
+        self.write_row(f, header.iter().copied(), &widths)?;
+        match self.style {
+            TableStyle::Gfm => {
+                self.write_row(f, header.iter().map(|_| "-"), &widths)?
+            }
+            TableStyle::Pandoc => {
+                write!(f, "|")?;
+                for width in &widths {
+                    write!(f, ":{}|", "-".repeat(width + 1))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        for row in &rows {
+            self.write_row(f, row.iter().copied(), &widths)?;
         }
         Ok(())
     }
@@ -154,44 +377,114 @@ mod test {
         );
     }
 
+    #[test]
+    fn relative_link_same_subdir() {
+        assert_eq!(
+            relative_link(
+                Path::new("references/foo.md"),
+                Path::new("references/foo/bar.md")
+            ),
+            "./foo/bar.md".to_string()
+        );
+    }
+
+    #[test]
+    fn links_plain() {
+        assert_eq!(Links::Plain.link("Foo", Some(Path::new("foo.md"))), "Foo");
+    }
+
+    #[test]
+    fn links_relative() {
+        let links = Links::RelativeTo(Path::new("welcome/day-1.md"));
+        assert_eq!(
+            links.link("Foo [1]", Some(Path::new("foo.md"))),
+            "[Foo \\[1\\]](../foo.md)"
+        );
+        assert_eq!(links.link("Foo", None), "Foo");
+        assert_eq!(
+            links.cell(Some(Path::new("welcome/foo.md"))).unwrap(),
+            "[→](./foo.md)"
+        );
+        assert_eq!(Links::Plain.cell(Some(Path::new("foo.md"))), None);
+    }
+
+    #[test]
+    fn speaker_notes_block() {
+        let content = "<details>\nA\n</details>\n\n<details open>\n\nB\n\n\
+                       <details>C</details>\n\n</details>\n";
+        assert_eq!(
+            &content[speaker_notes(content).unwrap()],
+            "\n\nB\n\n<details>C</details>\n\n"
+        );
+        assert_eq!(speaker_notes("```\n<details>\n```\n"), None);
+        assert_eq!(&"<details>\nA"[speaker_notes("<details>\nA").unwrap()], "\nA");
+    }
+
     #[test]
     fn duration_no_time() {
-        assert_eq!(duration(0), "0 minutes");
+        assert_eq!(duration(0, 5), "0 minutes");
     }
 
     #[test]
     fn duration_single_minute() {
-        assert_eq!(duration(1), "1 minute");
+        assert_eq!(duration(1, 5), "1 minute");
     }
 
     #[test]
     fn duration_two_minutes() {
-        assert_eq!(duration(2), "2 minutes");
+        assert_eq!(duration(2, 5), "2 minutes");
     }
 
     #[test]
     fn duration_seven_minutes() {
-        assert_eq!(duration(7), "10 minutes");
+        assert_eq!(duration(7, 5), "10 minutes");
     }
 
     #[test]
     fn duration_hour() {
-        assert_eq!(duration(60), "1 hour");
+        assert_eq!(duration(60, 5), "1 hour");
     }
 
     #[test]
     fn duration_hour_mins() {
-        assert_eq!(duration(61), "1 hour and 5 minutes");
+        assert_eq!(duration(61, 5), "1 hour and 5 minutes");
     }
 
     #[test]
     fn duration_hours() {
-        assert_eq!(duration(120), "2 hours");
+        assert_eq!(duration(120, 5), "2 hours");
     }
 
     #[test]
     fn duration_hours_mins() {
-        assert_eq!(duration(130), "2 hours and 10 minutes");
+        assert_eq!(duration(130, 5), "2 hours and 10 minutes");
+    }
+
+    #[test]
+    fn duration_rounding() {
+        assert_eq!(duration(7, 1), "7 minutes");
+        assert_eq!(duration(61, 15), "1 hour and 15 minutes");
+        assert_eq!(duration(10, 15), "10 minutes");
+    }
+
+    #[test]
+    fn suggestions() {
+        let candidates = ["minutes", "target_minutes", "session"];
+        assert_eq!(suggest("minute", candidates), Some("minutes"));
+        assert_eq!(suggest("target-minutes", candidates), Some("target_minutes"));
+        assert_eq!(suggest("course", candidates), None);
+    }
+
+    #[test]
+    fn strip_notes() {
+        assert_eq!(
+            strip_speaker_notes(
+                "# Slide\n\n<details>\n\nNotes <details>x</details>\n\n</details>\n\n\
+                 ```html\n<details>\n```\n\n<details>\n\nMore notes.\n"
+            ),
+            "# Slide\n\n<details>\n\nNotes <details>x</details>\n\n</details>\n\n\
+             ```html\n<details>\n```\n"
+        );
     }
 
     #[test]
@@ -204,4 +497,27 @@ mod test {
             "| a | b |\n| - | - |\n| a1 | b1 |\n| a2 | b2 |\n"
         );
     }
+
+    #[test]
+    fn linked_table() {
+        let mut table = Table::new(["a".into()]);
+        table.add_linked_row(["a1".into()], Some("[→](./a1.md)".into()));
+        table.add_row(["a2".into()]);
+        assert_eq!(
+            table.to_string(),
+            "| a |  |\n| - | - |\n| a1 | [→](./a1.md) |\n| a2 |  |\n"
+        );
+    }
+
+    #[test]
+    fn pandoc_table() {
+        let mut table =
+            Table::new(["a".into(), "bb".into()]).with_style(TableStyle::Pandoc);
+        table.add_row(["a1".into(), "b1".into()]);
+        table.add_row(["a22".into(), "b2".into()]);
+        assert_eq!(
+            table.to_string(),
+            "| a   | bb |\n|:----|:---|\n| a1  | b1 |\n| a22 | b2 |\n"
+        );
+    }
 }
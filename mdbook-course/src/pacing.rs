@@ -0,0 +1,387 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of the declared minutes of each slide with the volume of its
+//! content.
+//!
+//! Each timed slide is given a "pace": the logarithm of its minutes per unit of
+//! content volume. Slides whose pace is far from the median pace of their
+//! course, as measured by the modified z-score (Iglewicz and Hoaglin), are
+//! flagged as outliers.
+
+use crate::course::{Course, Courses, Slide};
+use crate::csv;
+use lazy_static::lazy_static;
+use mdbook::book::{Book, BookItem};
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::AddAssign;
+use std::path::Path;
+
+lazy_static! {
+    static ref HTML_TAG: Regex = Regex::new(r"<(/?)([a-zA-Z]*)[^>]*>").unwrap();
+}
+
+/// Each line of code counts as this many words of content volume.
+const CODE_LINE_WORDS: u64 = 5;
+
+/// Modified z-scores with a magnitude above this are outliers.
+const OUTLIER_THRESHOLD: f64 = 3.5;
+
+/// Outliers are only flagged in courses with at least this many scored slides.
+const MIN_SAMPLE: usize = 5;
+
+/// Measures of the content of a slide.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// Words of text shown on the slide, excluding code blocks.
+    pub body_words: u64,
+    /// Words of speaker notes, inside `<details>`.
+    pub notes_words: u64,
+    pub code_blocks: u64,
+    pub code_lines: u64,
+    /// Rust code blocks which can be run in the playground.
+    pub playground_snippets: u64,
+}
+
+impl Metrics {
+    /// Measure the given Markdown content, which should not include
+    /// frontmatter.
+    pub fn from_markdown(content: &str) -> Self {
+        let mut metrics = Metrics::default();
+        let mut details_depth = 0;
+        let mut in_code_block = false;
+        for event in Parser::new(content) {
+            match event {
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    in_code_block = true;
+                    metrics.code_blocks += 1;
+                    if let CodeBlockKind::Fenced(info) = kind
+                        && is_playground(&info)
+                    {
+                        metrics.playground_snippets += 1;
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => in_code_block = false,
+                Event::Text(text) if in_code_block => {
+                    metrics.code_lines += text.lines().count() as u64;
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    metrics.add_words(&text, details_depth > 0);
+                }
+                Event::Html(html) | Event::InlineHtml(html) => {
+                    // Text may appear between tags in the same HTML block, so
+                    // split the block at each tag.
+                    let mut last = 0;
+                    for tag in HTML_TAG.captures_iter(&html) {
+                        let whole = tag.get(0).unwrap();
+                        metrics.add_words(
+                            &html[last..whole.start()],
+                            details_depth > 0,
+                        );
+                        last = whole.end();
+                        if tag[2].eq_ignore_ascii_case("details") {
+                            if tag[1].is_empty() {
+                                details_depth += 1;
+                            } else {
+                                details_depth -= 1;
+                            }
+                        }
+                    }
+                    metrics.add_words(&html[last..], details_depth > 0);
+                }
+                _ => {}
+            }
+        }
+        metrics
+    }
+
+    fn add_words(&mut self, text: &str, notes: bool) {
+        let words = text.split_whitespace().count() as u64;
+        if notes {
+            self.notes_words += words;
+        } else {
+            self.body_words += words;
+        }
+    }
+
+    /// The overall volume of content, in words.
+    pub fn volume(&self) -> u64 {
+        self.body_words + self.notes_words + self.code_lines * CODE_LINE_WORDS
+    }
+}
+
+impl AddAssign for Metrics {
+    fn add_assign(&mut self, other: Self) {
+        self.body_words += other.body_words;
+        self.notes_words += other.notes_words;
+        self.code_blocks += other.code_blocks;
+        self.code_lines += other.code_lines;
+        self.playground_snippets += other.playground_snippets;
+    }
+}
+
+/// Determine whether a fenced code block with the given info string gets a
+/// playground "run" button, following mdbook's rules.
+fn is_playground(info: &str) -> bool {
+    let mut attributes = info.split([',', ' ', '\t']).filter(|a| !a.is_empty());
+    attributes.next() == Some("rust")
+        && !attributes.any(|a| a == "ignore" || a == "noplayground")
+}
+
+/// The direction in which a slide's declared minutes are unusual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outlier {
+    /// More minutes than the slide's content suggests.
+    Long,
+    /// Fewer minutes than the slide's content suggests.
+    Short,
+}
+
+impl fmt::Display for Outlier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outlier::Long => write!(f, "long"),
+            Outlier::Short => write!(f, "short"),
+        }
+    }
+}
+
+/// The pacing of a single slide within a course.
+#[derive(Debug)]
+pub struct SlidePacing<'a> {
+    pub course: &'a Course,
+    pub slide: &'a Slide,
+    pub metrics: Metrics,
+    /// Modified z-score of the slide's pace, if it has both minutes and
+    /// content.
+    pub score: Option<f64>,
+    pub outlier: Option<Outlier>,
+}
+
+/// Pacing of all slides in all courses.
+#[derive(Debug)]
+pub struct PacingReport<'a> {
+    pub slides: Vec<SlidePacing<'a>>,
+}
+
+impl<'a> PacingReport<'a> {
+    /// Measure every slide in the given courses, using the chapter content in
+    /// the book.
+    pub fn new(courses: &'a Courses, book: &Book) -> Self {
+        let mut content: HashMap<&Path, &str> = HashMap::new();
+        for item in book.iter() {
+            if let BookItem::Chapter(chapter) = item
+                && let Some(source_path) = &chapter.source_path
+            {
+                content.insert(source_path, &chapter.content);
+            }
+        }
+
+        let mut slides = Vec::new();
+        for course in courses {
+            let first = slides.len();
+            for session in course {
+                for segment in session {
+                    for slide in segment {
+                        let mut metrics = Metrics::default();
+                        for source_path in &slide.source_paths {
+                            if let Some(content) = content.get(source_path.as_path())
+                            {
+                                metrics += Metrics::from_markdown(content);
+                            }
+                        }
+                        slides.push(SlidePacing {
+                            course,
+                            slide,
+                            metrics,
+                            score: None,
+                            outlier: None,
+                        });
+                    }
+                }
+            }
+            score(&mut slides[first..]);
+        }
+        PacingReport { slides }
+    }
+
+    /// Render the report as a table with aligned columns, for a terminal.
+    pub fn to_table(&self) -> String {
+        let mut rows = vec![HEADER.map(String::from).to_vec()];
+        rows.extend(self.slides.iter().map(|s| s.fields().to_vec()));
+        let widths: Vec<usize> = (0..HEADER.len())
+            .map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap())
+            .collect();
+
+        let mut out = String::new();
+        for row in rows {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(field, width)| format!("{field:width$}"))
+                .collect();
+            out.push_str(line.join("  ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render the report as CSV.
+    pub fn to_csv(&self) -> String {
+        let mut out = csv::record(HEADER);
+        for slide in &self.slides {
+            out.push_str(&csv::record(slide.fields()));
+        }
+        out
+    }
+}
+
+const HEADER: [&str; 11] = [
+    "course",
+    "slide",
+    "source",
+    "minutes",
+    "body words",
+    "notes words",
+    "code blocks",
+    "code lines",
+    "playground",
+    "score",
+    "outlier",
+];
+
+impl SlidePacing<'_> {
+    fn fields(&self) -> [String; 11] {
+        [
+            self.course.name.clone(),
+            self.slide.name.clone(),
+            self.slide
+                .source_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            self.slide.minutes().to_string(),
+            self.metrics.body_words.to_string(),
+            self.metrics.notes_words.to_string(),
+            self.metrics.code_blocks.to_string(),
+            self.metrics.code_lines.to_string(),
+            self.metrics.playground_snippets.to_string(),
+            self.score.map(|s| format!("{s:.2}")).unwrap_or_default(),
+            self.outlier.map(|o| o.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+/// Compute the modified z-score of the pace of each slide in a course, and flag
+/// outliers.
+fn score(slides: &mut [SlidePacing]) {
+    let paces: Vec<(usize, f64)> = slides
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.slide.minutes() > 0 && s.metrics.volume() > 0)
+        .map(|(i, s)| {
+            (i, (s.slide.minutes() as f64 / s.metrics.volume() as f64).ln())
+        })
+        .collect();
+    let median_pace = median(paces.iter().map(|(_, pace)| *pace).collect());
+    let mad =
+        median(paces.iter().map(|(_, pace)| (pace - median_pace).abs()).collect());
+    if mad == 0.0 {
+        return;
+    }
+    for (i, pace) in &paces {
+        let score = 0.6745 * (pace - median_pace) / mad;
+        slides[*i].score = Some(score);
+        if paces.len() >= MIN_SAMPLE && score.abs() > OUTLIER_THRESHOLD {
+            slides[*i].outlier =
+                Some(if score > 0.0 { Outlier::Long } else { Outlier::Short });
+        }
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use mdbook::book::Chapter;
+
+    #[test]
+    fn metrics() {
+        let content = "# Title\n\nSome `body` text.\n\n\
+            ```rust\nfn main() {\n}\n```\n\n\
+            ```rust,ignore\nfoo();\n```\n\n\
+            ```rust,editable\nbar();\n```\n\n\
+            <details>\n\nThree note words.\n\n</details>\n\n\
+            <details>Inline note</details> after";
+        assert_eq!(
+            Metrics::from_markdown(content),
+            Metrics {
+                body_words: 5,
+                notes_words: 5,
+                code_blocks: 3,
+                code_lines: 4,
+                playground_snippets: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn outliers() {
+        let mut book = Book::new();
+        let mut push = |name: &str, frontmatter: &str, words: usize| {
+            let content =
+                format!("---\n{frontmatter}\n---\n{}", "word ".repeat(words));
+            book.push_item(Chapter::new(
+                name,
+                content,
+                format!("{name}.md"),
+                Vec::new(),
+            ));
+        };
+        push("a", "course: C\nsession: S\nminutes: 5", 100);
+        let mut words = |name: &str, minutes: u64, words: usize| {
+            push(name, &format!("minutes: {minutes}"), words)
+        };
+        words("b", 4, 90);
+        words("c", 6, 110);
+        words("d", 5, 95);
+        words("e", 5, 105);
+        words("f", 30, 100);
+        words("g", 1, 100);
+        let (courses, book) =
+            Courses::extract_structure(book, &Config::default()).unwrap();
+        let report = PacingReport::new(&courses, &book);
+        let outliers: Vec<_> = report
+            .slides
+            .iter()
+            .filter_map(|s| Some((s.slide.name.as_str(), s.outlier?)))
+            .collect();
+        assert_eq!(outliers, vec![("f", Outlier::Long), ("g", Outlier::Short)]);
+    }
+}
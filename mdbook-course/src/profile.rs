@@ -0,0 +1,184 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Build profiles, which produce the instructor and student editions of the
+//! book from the same source.
+//!
+//! The instructor edition is the book as written. The student edition has no
+//! speaker notes or timing information, and its solutions are left out or
+//! withheld until a configured date. Outlines and schedules are the same in
+//! both editions.
+
+use crate::config::StudentConfig;
+use crate::markdown::strip_speaker_notes;
+use chrono::NaiveDate;
+use mdbook::book::{Book, BookItem, Chapter};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// The environment variable selecting the profile, overriding `profile` in
+/// `book.toml`.
+pub const PROFILE_VAR: &str = "MDBOOK_COURSE_PROFILE";
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    #[default]
+    Instructor,
+    Student,
+}
+
+impl FromStr for Profile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "instructor" => Ok(Profile::Instructor),
+            "student" => Ok(Profile::Student),
+            _ => anyhow::bail!(
+                "Unknown profile {s:?}; expected `instructor` or `student`"
+            ),
+        }
+    }
+}
+
+impl Profile {
+    /// Determine the profile from [`PROFILE_VAR`], falling back to the
+    /// configured profile.
+    pub fn from_env(configured: Profile) -> anyhow::Result<Profile> {
+        match std::env::var(PROFILE_VAR) {
+            Ok(profile) if !profile.is_empty() => profile.parse(),
+            _ => Ok(configured),
+        }
+    }
+
+    /// Whether timing information is added to speaker notes.
+    pub fn has_timing(self) -> bool {
+        self == Profile::Instructor
+    }
+}
+
+/// Determine whether a chapter is the solution to an exercise.
+pub fn is_solution(chapter: &Chapter) -> bool {
+    chapter.source_path.as_ref().is_some_and(|path| {
+        path.file_name().is_some_and(|name| name == "solution.md")
+    })
+}
+
+/// Turn the preprocessed book into the student edition, as of `today`.
+pub fn student_edition(book: &mut Book, config: &StudentConfig, today: NaiveDate) {
+    let withheld_until = config.solutions_from.filter(|date| today < *date);
+    if config.solutions_from.is_none() {
+        remove_solutions(&mut book.sections);
+    }
+    book.for_each_mut(|item| {
+        let BookItem::Chapter(chapter) = item else {
+            return;
+        };
+        if let Some(date) = withheld_until
+            && is_solution(chapter)
+        {
+            chapter.content = format!(
+                "# {}\n\nThe solution will be available on {date}.\n",
+                chapter.name
+            );
+        } else {
+            chapter.content = strip_speaker_notes(&chapter.content);
+        }
+    });
+}
+
+fn remove_solutions(items: &mut Vec<BookItem>) {
+    items.retain(
+        |item| !matches!(item, BookItem::Chapter(chapter) if is_solution(chapter)),
+    );
+    for item in items {
+        if let BookItem::Chapter(chapter) = item {
+            remove_solutions(&mut chapter.sub_items);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn book() -> Book {
+        let mut exercise = Chapter::new(
+            "Exercise",
+            "# Exercise\n\nWrite it.\n\n<details><summary>Hint</summary>\n\nUse a \
+             loop.\n\n</details>\n\n<details>\n\nNotes.\n\n</details>\n"
+                .into(),
+            "types/exercise.md",
+            Vec::new(),
+        );
+        let solution = Chapter::new(
+            "Solution",
+            "# Solution\n\n```rust\nfn main() {}\n```\n".into(),
+            "types/solution.md",
+            Vec::new(),
+        );
+        exercise.sub_items.push(BookItem::Chapter(solution));
+        let mut book = Book::new();
+        book.push_item(exercise);
+        book
+    }
+
+    fn contents(book: &Book) -> Vec<String> {
+        book.iter()
+            .filter_map(|item| match item {
+                BookItem::Chapter(chapter) => Some(chapter.content.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("student".parse::<Profile>().unwrap(), Profile::Student);
+        assert!("teacher".parse::<Profile>().is_err());
+    }
+
+    #[test]
+    fn solutions_left_out() {
+        let mut book = book();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        student_edition(&mut book, &StudentConfig::default(), today);
+        // Only the speaker notes are removed, not the hint.
+        assert_eq!(
+            contents(&book),
+            [
+                "# Exercise\n\nWrite it.\n\n<details><summary>Hint</summary>\n\nUse a \
+              loop.\n\n</details>\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn solutions_withheld() {
+        let config =
+            StudentConfig { solutions_from: NaiveDate::from_ymd_opt(2026, 10, 2) };
+        let mut withheld = book();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        student_edition(&mut withheld, &config, today);
+        assert_eq!(
+            contents(&withheld)[1],
+            "# Solution\n\nThe solution will be available on 2026-10-02.\n"
+        );
+
+        let mut released = book();
+        student_edition(&mut released, &config, today.succ_opt().unwrap());
+        assert!(contents(&released)[1].contains("fn main"));
+    }
+}
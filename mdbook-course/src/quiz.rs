@@ -0,0 +1,380 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Quiz questions, written as fenced code blocks with the `quiz` info string
+//! containing YAML:
+//!
+//! ````markdown
+//! ```quiz
+//! question: What is the type of `x` in `let x = 5;`?
+//! options: ["`i32`", "`u32`", "`usize`"]
+//! answer: "`i32`"
+//! explanation: Integer literals default to `i32`.
+//! minutes: 2
+//! ```
+//! ````
+//!
+//! The question, options and explanation are Markdown. Each quiz takes
+//! `minutes` (by default, 1) of the minutes of its slide, which the
+//! `quiz-minutes` lint rule checks are enough for its quizzes.
+
+use crate::course::Course;
+use crate::markdown::Table;
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd, html};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::Path;
+
+/// A multiple-choice question.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Quiz {
+    pub question: String,
+    pub options: Vec<String>,
+    /// The correct option.
+    pub answer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    /// Minutes to allow for the quiz.
+    #[serde(default = "default_minutes")]
+    pub minutes: u64,
+}
+
+fn default_minutes() -> u64 {
+    1
+}
+
+impl Quiz {
+    /// Return the index of the correct option.
+    pub fn answer_index(&self) -> usize {
+        self.options.iter().position(|o| *o == self.answer).unwrap_or_default()
+    }
+}
+
+/// Find the quiz blocks in the content, with the range of each block
+/// (including its fences) and its YAML.
+fn quiz_blocks(content: &str) -> Vec<(Range<usize>, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (event, range) in Parser::new(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                if info.split([' ', ',']).next() == Some("quiz") =>
+            {
+                current = Some((range.start, String::new()));
+            }
+            Event::Text(text) => {
+                if let Some((_, yaml)) = &mut current {
+                    yaml.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((start, yaml)) = current.take() {
+                    blocks.push((start..range.end, yaml));
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+fn parse(yaml: &str) -> anyhow::Result<Quiz> {
+    let quiz: Quiz = serde_yaml::from_str(yaml)?;
+    if quiz.options.len() < 2 {
+        anyhow::bail!("a quiz needs at least two options");
+    }
+    if !quiz.options.contains(&quiz.answer) {
+        anyhow::bail!("answer {:?} is not one of the options", quiz.answer);
+    }
+    Ok(quiz)
+}
+
+/// Parse the quizzes in the content.
+pub fn parse_quizzes(content: &str) -> anyhow::Result<Vec<Quiz>> {
+    quiz_blocks(content)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (_, yaml))| {
+            parse(&yaml).map_err(|e| anyhow::anyhow!("quiz {}: {e}", idx + 1))
+        })
+        .collect()
+}
+
+/// Replace the quiz blocks in the content with interactive HTML forms, or
+/// with plain Markdown if `interactive` is false. `id` distinguishes the
+/// chapter's quizzes from those of other chapters on the same page.
+pub fn render_quizzes(
+    content: &str,
+    id: &str,
+    interactive: bool,
+) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for (idx, (range, yaml)) in quiz_blocks(content).into_iter().enumerate() {
+        let quiz =
+            parse(&yaml).map_err(|e| anyhow::anyhow!("quiz {}: {e}", idx + 1))?;
+        out.push_str(&content[last..range.start]);
+        let rendered = if interactive {
+            to_html(&quiz, &format!("{id}-{}", idx + 1))
+        } else {
+            to_markdown(&quiz)
+        };
+        // The block's range ends before its final newline.
+        out.push_str(rendered.trim_end_matches('\n'));
+        last = range.end;
+    }
+    out.push_str(&content[last..]);
+    Ok(out)
+}
+
+/// Render Markdown as HTML, without the enclosing paragraph if there is only
+/// one.
+fn inline_html(markdown: &str) -> String {
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new(markdown));
+    let out = out.trim_end();
+    match out.strip_prefix("<p>").and_then(|s| s.strip_suffix("</p>")) {
+        Some(inner) if !inner.contains("<p>") => inner.to_string(),
+        _ => out.to_string(),
+    }
+}
+
+/// The script run when a quiz form is submitted, showing whether the checked
+/// option is correct.
+const CHECK_SCRIPT: &str = "event.preventDefault();\
+    var checked = this.querySelector('input:checked');\
+    if (!checked) return;\
+    var correct = checked.value == this.dataset.answer;\
+    this.querySelector('.course-quiz-result').hidden = false;\
+    this.querySelector('.course-quiz-correct').hidden = !correct;\
+    this.querySelector('.course-quiz-incorrect').hidden = correct;";
+
+fn to_html(quiz: &Quiz, id: &str) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<form class=\"course-quiz\" data-answer=\"{}\" onsubmit=\"{CHECK_SCRIPT}\">",
+        quiz.answer_index()
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<p class=\"course-quiz-question\"><strong>Quiz:</strong> {}</p>",
+        inline_html(&quiz.question)
+    )
+    .unwrap();
+    for (idx, option) in quiz.options.iter().enumerate() {
+        writeln!(
+            out,
+            "<label><input type=\"radio\" name=\"{id}\" value=\"{idx}\"> {}</label><br>",
+            inline_html(option)
+        )
+        .unwrap();
+    }
+    writeln!(out, "<button type=\"submit\">Check</button>").unwrap();
+    writeln!(out, "<div class=\"course-quiz-result\" hidden>").unwrap();
+    writeln!(out, "<p class=\"course-quiz-correct\">Correct!</p>").unwrap();
+    writeln!(
+        out,
+        "<p class=\"course-quiz-incorrect\">Not quite. The correct answer is: {}</p>",
+        inline_html(&quiz.answer)
+    )
+    .unwrap();
+    if let Some(explanation) = &quiz.explanation {
+        writeln!(
+            out,
+            "<div class=\"course-quiz-explanation\">{}</div>",
+            inline_html(explanation)
+        )
+        .unwrap();
+    }
+    out.push_str("</div>\n</form>\n");
+    out
+}
+
+fn to_markdown(quiz: &Quiz) -> String {
+    let mut out = format!("**Quiz:** {}\n\n", quiz.question);
+    for option in &quiz.options {
+        writeln!(out, "- {option}").unwrap();
+    }
+    write!(out, "\n_Answer:_ {}", quiz.answer).unwrap();
+    if let Some(explanation) = &quiz.explanation {
+        write!(out, " {explanation}").unwrap();
+    }
+    out.push('\n');
+    out
+}
+
+/// A question in a quiz bank, with where it appears in the course.
+#[derive(Serialize, Debug)]
+pub struct BankQuestion<'a> {
+    pub session: &'a str,
+    pub segment: &'a str,
+    pub slide: &'a str,
+    pub source_path: Option<&'a Path>,
+    #[serde(flatten)]
+    pub quiz: &'a Quiz,
+}
+
+/// All of the quizzes in a course, for use outside of the book.
+#[derive(Serialize, Debug)]
+pub struct QuizBank<'a> {
+    pub course: &'a str,
+    pub questions: Vec<BankQuestion<'a>>,
+}
+
+impl<'a> QuizBank<'a> {
+    pub fn new(course: &'a Course) -> Self {
+        let mut questions = Vec::new();
+        for session in course {
+            for segment in session {
+                for (slide, quiz) in segment.quizzes() {
+                    questions.push(BankQuestion {
+                        session: &session.name,
+                        segment: &segment.name,
+                        slide: &slide.name,
+                        source_path: slide.source_path(),
+                        quiz,
+                    });
+                }
+            }
+        }
+        QuizBank { course: &course.name, questions }
+    }
+
+    /// Render the quiz bank as Markdown, with the questions of each segment
+    /// under a heading, the correct option checked, and a summary table.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.course);
+        if self.questions.is_empty() {
+            out.push_str("This course has no quizzes.\n\n");
+            return out;
+        }
+        let mut summary = Table::new([
+            "Session".into(),
+            "Segment".into(),
+            "Questions".into(),
+            "Minutes".into(),
+        ]);
+        let mut current = None;
+        for question in &self.questions {
+            let key = (question.session, question.segment);
+            if current != Some(key) {
+                current = Some(key);
+                writeln!(out, "## {} / {}\n", question.session, question.segment)
+                    .unwrap();
+                let in_segment =
+                    self.questions.iter().filter(|q| (q.session, q.segment) == key);
+                summary.add_row([
+                    question.session.into(),
+                    question.segment.into(),
+                    in_segment.clone().count().to_string(),
+                    in_segment.map(|q| q.quiz.minutes).sum::<u64>().to_string(),
+                ]);
+            }
+            writeln!(out, "### {}\n", question.quiz.question).unwrap();
+            for option in &question.quiz.options {
+                let check = if *option == question.quiz.answer { 'x' } else { ' ' };
+                writeln!(out, "- [{check}] {option}").unwrap();
+            }
+            out.push('\n');
+            if let Some(explanation) = &question.quiz.explanation {
+                writeln!(out, "> {}\n", explanation.trim().replace('\n', "\n> "))
+                    .unwrap();
+            }
+            if let Some(source_path) = question.source_path {
+                writeln!(out, "_{}: `{}`_\n", question.slide, source_path.display())
+                    .unwrap();
+            }
+        }
+        writeln!(out, "## Summary\n\n{summary}").unwrap();
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use crate::course::Courses;
+    use mdbook::book::{Book, Chapter};
+
+    const CONTENT: &str = "# Types\n\n```quiz\nquestion: What is `5`?\n\
+                           options: [\"`i32`\", \"`u8`\"]\nanswer: \"`i32`\"\n\
+                           explanation: The default.\n```\n\nMore text.\n";
+
+    #[test]
+    fn parse_and_validate() {
+        let quizzes = parse_quizzes(CONTENT).unwrap();
+        assert_eq!(quizzes.len(), 1);
+        assert_eq!(quizzes[0].answer_index(), 0);
+        assert_eq!(quizzes[0].minutes, 1);
+        assert!(
+            parse_quizzes("```quiz\nquestion: Q\noptions: [a, b]\nanswer: c\n```")
+                .is_err()
+        );
+        assert!(
+            parse_quizzes("```quiz\nquestion: Q\noptions: [a]\nanswer: a\n```")
+                .is_err()
+        );
+        assert!(parse_quizzes("```rust\nfn main() {}\n```").unwrap().is_empty());
+    }
+
+    #[test]
+    fn render() {
+        let html = render_quizzes(CONTENT, "quiz-types", true).unwrap();
+        assert!(html.starts_with(
+            "# Types\n\n<form class=\"course-quiz\" data-answer=\"0\""
+        ));
+        assert!(html.contains("<input type=\"radio\" name=\"quiz-types-1\" value=\"1\"> <code>u8</code></label>"));
+        assert!(html.ends_with("</form>\n\nMore text.\n"));
+        assert!(!html.contains("\n\n<label"));
+
+        assert_eq!(
+            render_quizzes(CONTENT, "quiz-types", false).unwrap(),
+            "# Types\n\n**Quiz:** What is `5`?\n\n- `i32`\n- `u8`\n\n\
+             _Answer:_ `i32` The default.\n\nMore text.\n"
+        );
+    }
+
+    #[test]
+    fn quiz_bank() {
+        let mut book = Book::new();
+        book.push_item(Chapter::new(
+            "Types",
+            format!("---\ncourse: C\nsession: S\nminutes: 5\n---\n{CONTENT}"),
+            "types.md",
+            Vec::new(),
+        ));
+        let (courses, _) =
+            Courses::extract_structure(book, &Config::default()).unwrap();
+        let course = &courses.courses[0];
+        assert_eq!(course.minutes(), 5);
+
+        let bank = QuizBank::new(course);
+        assert_eq!(bank.questions.len(), 1);
+        assert_eq!(
+            bank.to_markdown(),
+            "# C\n\n## S / Types\n\n### What is `5`?\n\n- [x] `i32`\n- [ ] `u8`\n\n\
+             > The default.\n\n_Types: `types.md`_\n\n## Summary\n\n\
+             | Session | Segment | Questions | Minutes |\n| - | - | - | - |\n\
+             | S | Types | 1 | 1 |\n\n"
+        );
+        let json = serde_json::to_value(&bank).unwrap();
+        assert_eq!(json["questions"][0]["answer"], "`i32`");
+        assert_eq!(json["questions"][0]["segment"], "Types");
+    }
+}
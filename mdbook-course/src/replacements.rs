@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::course::{Course, Courses, Segment, Session};
+use crate::chart;
+use crate::config::RendererOptions;
+use crate::course::{Course, Courses, Segment, Session, Slide};
+use crate::markdown::{Links, Table, duration};
 use mdbook::book::Chapter;
 use regex::Regex;
 
@@ -20,43 +23,315 @@ lazy_static::lazy_static! {
     static ref DIRECTIVE: Regex = Regex::new(r#"\{\{%([^}]*)}}"#).unwrap();
 }
 
+/// A directive, of the form `{{%...}}`, in the course material.
+#[derive(Debug, PartialEq, Eq)]
+enum Directive {
+    SessionOutline,
+    SegmentOutline,
+    /// An outline of the named course, or of the current course.
+    CourseOutline(Option<String>),
+    SessionDuration,
+    /// The duration of the named course, or of the current course.
+    CourseDuration(Option<String>),
+    /// A chart of the sessions in the named course, or the current course.
+    CourseChart(Option<String>),
+    SessionChart,
+    SlideMinutes,
+    NextSegment,
+    PreviousSegment,
+    CourseList,
+}
+
+impl Directive {
+    fn parse(directive: &str) -> anyhow::Result<Self> {
+        let words: Vec<_> = directive.split_whitespace().collect();
+        let course_name = |words: &[&str]| {
+            if words.is_empty() { None } else { Some(words.join(" ")) }
+        };
+        Ok(match words.as_slice() {
+            ["session", "outline"] => Directive::SessionOutline,
+            ["segment", "outline"] => Directive::SegmentOutline,
+            ["course", "outline", name @ ..] => {
+                Directive::CourseOutline(course_name(name))
+            }
+            ["course", "chart", name @ ..] => {
+                Directive::CourseChart(course_name(name))
+            }
+            ["session", "chart"] => Directive::SessionChart,
+            ["session", "duration"] => Directive::SessionDuration,
+            ["course", "duration", name @ ..] => {
+                Directive::CourseDuration(course_name(name))
+            }
+            ["slide", "minutes"] => Directive::SlideMinutes,
+            ["next", "segment"] => Directive::NextSegment,
+            ["previous", "segment"] => Directive::PreviousSegment,
+            ["course", "list"] => Directive::CourseList,
+            _ => anyhow::bail!("unknown directive {directive:?}"),
+        })
+    }
+}
+
+/// The context in which directives are replaced.
+struct Context<'a> {
+    courses: &'a Courses,
+    course: Option<&'a Course>,
+    session: Option<&'a Session>,
+    segment: Option<&'a Segment>,
+    slide: Option<&'a Slide>,
+    links: Links<'a>,
+    /// The options for the renderer, including whether it is HTML, which can
+    /// show SVG charts.
+    renderer: &'a RendererOptions,
+}
+
+impl Context<'_> {
+    /// Get a course or course variant by name, or the current course.
+    fn course(&self, name: &Option<String>) -> anyhow::Result<&Course> {
+        match name {
+            Some(name) => self
+                .courses
+                .find_course(name)
+                .or_else(|| self.courses.find_variant(name))
+                .ok_or_else(|| anyhow::anyhow!("course {name:?} not found")),
+            None => require(self.course, "course"),
+        }
+    }
+
+    /// Generate a Markdown link to the given segment.
+    fn segment_link(&self, segment: Option<&Segment>) -> String {
+        let Some(segment) = segment else {
+            return String::new();
+        };
+        self.links.link(&segment.name, segment.source_path())
+    }
+
+    fn expand(&self, directive: &Directive) -> anyhow::Result<String> {
+        Ok(match directive {
+            Directive::SessionOutline => {
+                require(self.session, "session")?.outline(self.links, self.renderer)
+            }
+            Directive::SegmentOutline => {
+                require(self.segment, "segment")?.outline(self.links, self.renderer)
+            }
+            Directive::CourseOutline(name) => {
+                self.course(name)?.schedule(self.links, self.renderer)
+            }
+            // Charts are SVG, so other renderers get the equivalent outline.
+            Directive::CourseChart(name) if self.renderer.html => {
+                chart::sessions_chart(self.course(name)?, self.renderer.rounding)
+            }
+            Directive::CourseChart(name) => {
+                self.course(name)?.schedule(self.links, self.renderer)
+            }
+            Directive::SessionChart if self.renderer.html => {
+                chart::session_segments_chart(
+                    require(self.session, "session")?,
+                    self.renderer.rounding,
+                )
+            }
+            Directive::SessionChart => {
+                require(self.session, "session")?.outline(self.links, self.renderer)
+            }
+            Directive::SessionDuration => duration(
+                require(self.session, "session")?.minutes(),
+                self.renderer.rounding,
+            ),
+            Directive::CourseDuration(name) => {
+                duration(self.course(name)?.minutes(), self.renderer.rounding)
+            }
+            Directive::SlideMinutes => duration(
+                require(self.slide, "slide")?.minutes(),
+                self.renderer.rounding,
+            ),
+            Directive::NextSegment => {
+                let course = require(self.course, "course")?;
+                let segment = require(self.segment, "segment")?;
+                self.segment_link(course.next_segment(segment))
+            }
+            Directive::PreviousSegment => {
+                let course = require(self.course, "course")?;
+                let segment = require(self.segment, "segment")?;
+                self.segment_link(course.previous_segment(segment))
+            }
+            Directive::CourseList => {
+                let mut table = Table::new([
+                    "Course".into(),
+                    "Sessions".into(),
+                    "Duration".into(),
+                ])
+                .with_style(self.renderer.tables);
+                for course in self.courses {
+                    table.add_row([
+                        course.name.clone(),
+                        course.sessions.len().to_string(),
+                        duration(course.minutes(), self.renderer.rounding),
+                    ]);
+                }
+                table.to_string()
+            }
+        })
+    }
+}
+
+/// Require that the given part of the context is present.
+fn require<'a, T>(value: Option<&'a T>, what: &str) -> anyhow::Result<&'a T> {
+    value
+        .ok_or_else(|| anyhow::anyhow!("directive can only be used within a {what}"))
+}
+
 /// Replace supported directives with the relevant content.
 ///
+/// Unknown or malformed directives, or directives used outside of the context
+/// they require, are an error.
+///
+/// The renderer's options determine whether outlines link to the segments and
+/// slides they mention, or contain only plain text for renderers which cannot
+/// follow links between chapters, and whether charts are drawn or given as
+/// outlines.
+///
 /// See the mdbook-course README for details.
-#[allow(unused_variables)]
 pub fn replace(
     courses: &Courses,
     course: Option<&Course>,
     session: Option<&Session>,
     segment: Option<&Segment>,
+    slide: Option<&Slide>,
+    renderer: &RendererOptions,
     chapter: &mut Chapter,
-) {
+) -> anyhow::Result<()> {
     let Some(source_path) = &chapter.source_path else {
-        return;
+        return Ok(());
     };
-    chapter.content = DIRECTIVE
-        .replace_all(&chapter.content, |captures: &regex::Captures| {
-            let directive_str = captures[1].trim();
-            let directive: Vec<_> = directive_str.split_whitespace().collect();
-            match directive.as_slice() {
-                ["session", "outline"] if session.is_some() => {
-                    session.unwrap().outline()
-                }
-                ["segment", "outline"] if segment.is_some() => {
-                    segment.unwrap().outline()
-                }
-                ["course", "outline"] if course.is_some() => {
-                    course.unwrap().schedule()
-                }
-                ["course", "outline", course_name @ ..] => {
-                    let course_name = course_name.join(" ");
-                    let Some(course) = courses.find_course(course_name) else {
-                        return format!("not found - {}", &captures[0]);
-                    };
-                    course.schedule()
-                }
-                _ => directive_str.to_owned(),
-            }
-        })
-        .to_string();
+    let links =
+        if renderer.links { Links::RelativeTo(source_path) } else { Links::Plain };
+    let context =
+        Context { courses, course, session, segment, slide, links, renderer };
+
+    let mut content = String::with_capacity(chapter.content.len());
+    let mut last = 0;
+    for captures in DIRECTIVE.captures_iter(&chapter.content) {
+        let whole = captures.get(0).unwrap();
+        content.push_str(&chapter.content[last..whole.start()]);
+        let directive_str = captures[1].trim();
+        let replacement = Directive::parse(directive_str)
+            .and_then(|directive| context.expand(&directive))
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "{}: {{{{%{directive_str}}}}}: {e}",
+                    source_path.display()
+                )
+            })?;
+        content.push_str(&replacement);
+        last = whole.end();
+    }
+    content.push_str(&chapter.content[last..]);
+    chapter.content = content;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use crate::test_util::{chapter, courses};
+
+    fn html() -> RendererOptions {
+        Config::default().renderer("html")
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Directive::parse("session outline").unwrap(),
+            Directive::SessionOutline
+        );
+        assert_eq!(
+            Directive::parse("course outline Idiomatic Rust").unwrap(),
+            Directive::CourseOutline(Some("Idiomatic Rust".into()))
+        );
+        assert_eq!(
+            Directive::parse(" course  duration ").unwrap(),
+            Directive::CourseDuration(None)
+        );
+        assert_eq!(
+            Directive::parse("course chart").unwrap(),
+            Directive::CourseChart(None)
+        );
+        assert!(Directive::parse("session outlines").is_err());
+        assert!(Directive::parse("").is_err());
+    }
+
+    #[test]
+    fn replace_errors() {
+        let courses = Courses::default();
+        let mut chapter = Chapter::new(
+            "Test",
+            "{{%course list}} {{%bogus}}".into(),
+            "a/b.md",
+            Vec::new(),
+        );
+        let err = replace(&courses, None, None, None, None, &html(), &mut chapter)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a/b.md: {{%bogus}}: unknown directive \"bogus\""
+        );
+
+        let mut chapter = Chapter::new(
+            "Test",
+            "{{%session duration}}".into(),
+            "a/b.md",
+            Vec::new(),
+        );
+        let err = replace(&courses, None, None, None, None, &html(), &mut chapter)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a/b.md: {{%session duration}}: directive can only be used within a session"
+        );
+    }
+
+    #[test]
+    fn replace_course_list() {
+        let courses = Courses::default();
+        let mut chapter = Chapter::new(
+            "Test",
+            "A\n{{%course list}}\nB".into(),
+            "a/b.md",
+            Vec::new(),
+        );
+        replace(&courses, None, None, None, None, &html(), &mut chapter).unwrap();
+        assert_eq!(
+            chapter.content,
+            "A\n| Course | Sessions | Duration |\n| - | - | - |\n\nB"
+        );
+    }
+
+    #[test]
+    fn charts() {
+        let courses = courses([chapter(
+            "a",
+            "---\ncourse: C\nsession: S\nminutes: 5\n---\n",
+            Vec::new(),
+        )]);
+        let chart = |renderer: &RendererOptions| {
+            let mut chapter = Chapter::new(
+                "Test",
+                "{{%course chart C}}".into(),
+                "b.md",
+                Vec::new(),
+            );
+            replace(&courses, None, None, None, None, renderer, &mut chapter)
+                .unwrap();
+            chapter.content
+        };
+        assert!(chart(&html()).starts_with("<svg"));
+        // Links alone do not make a renderer able to show SVG.
+        let markdown = RendererOptions {
+            links: true,
+            ..Config::default().renderer("markdown")
+        };
+        assert!(!chart(&markdown).contains("<svg"));
+        assert!(chart(&markdown).starts_with("Course schedule:"));
+    }
 }
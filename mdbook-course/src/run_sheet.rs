@@ -0,0 +1,244 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Printable run-sheets for instructors, listing every slide in a session with
+//! its planned start and the text of its speaker notes.
+
+use crate::course::{Course, Session};
+use crate::markdown::{Table, duration, slug, speaker_notes};
+use mdbook::book::{Book, BookItem};
+use pulldown_cmark::{Options, Parser, html};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// An item in a run-sheet.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Entry<'a> {
+    Slide {
+        segment: &'a str,
+        name: &'a str,
+        /// Minutes since the start of the session.
+        start: u64,
+        minutes: u64,
+        /// The speaker notes of the slide and its sub-slides, as Markdown.
+        notes: String,
+    },
+    Break {
+        start: u64,
+        minutes: u64,
+    },
+}
+
+/// The run-sheet for a single session.
+#[derive(Debug)]
+pub struct RunSheet<'a> {
+    pub course: &'a Course,
+    pub session: &'a Session,
+    pub entries: Vec<Entry<'a>>,
+    /// Granularity, in minutes, to which the session's duration is rounded.
+    pub rounding: u64,
+}
+
+impl<'a> RunSheet<'a> {
+    /// Create a run-sheet for the given session, using the chapter content in
+    /// the book and rounding durations to `rounding` minutes.
+    pub fn new(
+        course: &'a Course,
+        session: &'a Session,
+        book: &Book,
+        rounding: u64,
+    ) -> Self {
+        let mut content: HashMap<&Path, &str> = HashMap::new();
+        for item in book.iter() {
+            if let BookItem::Chapter(chapter) = item
+                && let Some(source_path) = &chapter.source_path
+            {
+                content.insert(source_path, &chapter.content);
+            }
+        }
+
+        let breaks: Vec<_> = session.timed_segments().collect();
+        let mut entries = Vec::new();
+        let mut start = 0;
+        for segment in session {
+            for slide in segment {
+                let notes: Vec<&str> = slide
+                    .source_paths
+                    .iter()
+                    .filter_map(|path| content.get(path.as_path()))
+                    .filter_map(|content| {
+                        Some(content[speaker_notes(content)?].trim())
+                    })
+                    .filter(|notes| !notes.is_empty())
+                    .collect();
+                entries.push(Entry::Slide {
+                    segment: &segment.name,
+                    name: &slide.name,
+                    start,
+                    minutes: slide.minutes(),
+                    notes: notes.join("\n\n"),
+                });
+                start += slide.minutes();
+            }
+            let break_after = breaks
+                .iter()
+                .find(|(s, _)| std::ptr::eq(*s, segment))
+                .and_then(|(_, b)| *b)
+                .unwrap_or(0);
+            if break_after > 0 {
+                entries.push(Entry::Break { start, minutes: break_after });
+                start += break_after;
+            }
+        }
+        RunSheet { course, session, entries, rounding }
+    }
+
+    /// A name for files containing this run-sheet, without an extension.
+    pub fn file_stem(&self) -> String {
+        slug(&format!("{}-{}", self.course.name, self.session.name))
+    }
+
+    fn title(&self) -> String {
+        format!("{}: {}", self.course.name, self.session.name)
+    }
+
+    /// Render the run-sheet as Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title());
+        writeln!(
+            out,
+            "This session should take about {}.\n",
+            duration(self.session.minutes(), self.rounding)
+        )
+        .unwrap();
+
+        let mut table = Table::new([
+            "Start".into(),
+            "Segment".into(),
+            "Slide".into(),
+            "Minutes".into(),
+        ]);
+        for entry in &self.entries {
+            table.add_row(match entry {
+                Entry::Slide { segment, name, start, minutes, .. } => [
+                    offset(*start),
+                    segment.to_string(),
+                    name.to_string(),
+                    minutes.to_string(),
+                ],
+                Entry::Break { start, minutes } => [
+                    offset(*start),
+                    "Break".into(),
+                    String::new(),
+                    minutes.to_string(),
+                ],
+            });
+        }
+        writeln!(out, "{table}").unwrap();
+
+        let mut current_segment = None;
+        for entry in &self.entries {
+            let Entry::Slide { segment, name, start, minutes, notes } = entry else {
+                continue;
+            };
+            if current_segment != Some(segment) {
+                writeln!(out, "## {segment}\n").unwrap();
+                current_segment = Some(segment);
+            }
+            writeln!(out, "### {} {name} ({minutes} min)\n", offset(*start))
+                .unwrap();
+            if !notes.is_empty() {
+                writeln!(out, "{notes}\n").unwrap();
+            }
+        }
+        out
+    }
+
+    /// Render the run-sheet as a standalone HTML document.
+    pub fn to_html(&self) -> String {
+        let markdown = self.to_markdown();
+        let mut body = String::new();
+        html::push_html(
+            &mut body,
+            Parser::new_ext(&markdown, Options::ENABLE_TABLES),
+        );
+        let title = self
+            .title()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>\n{STYLE}</style>\n</head>\n\
+             <body>\n{body}</body>\n</html>\n"
+        )
+    }
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 50em; margin: auto; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; }
+";
+
+/// Format an offset from the start of a session, as H:MM.
+fn offset(minutes: u64) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{book, chapter, extract};
+
+    #[test]
+    fn run_sheet() {
+        let (courses, book) = extract(book([
+            chapter(
+                "a",
+                "---\ncourse: Course\nsession: Day 1\nminutes: 5\n---\n# A\n\
+                 <details>\n\nNotes for *A*.\n\n</details>",
+                vec![chapter(
+                    "b",
+                    "---\nminutes: 70\n---\n# B\n<details>\n</details>",
+                    Vec::new(),
+                )],
+            ),
+            chapter("c", "---\nminutes: 10\n---\n# C", Vec::new()),
+        ]));
+        let course = courses.find_course("Course").unwrap();
+        let run_sheet = RunSheet::new(course, &course.sessions[0], &book, 5);
+
+        assert_eq!(run_sheet.file_stem(), "course-day-1");
+        assert_eq!(
+            run_sheet.to_markdown(),
+            "# Course: Day 1\n\n\
+             This session should take about 1 hour and 35 minutes.\n\n\
+             | Start | Segment | Slide | Minutes |\n\
+             | - | - | - | - |\n\
+             | 0:00 | a | a | 5 |\n\
+             | 0:05 | a | b | 70 |\n\
+             | 1:15 | Break |  | 10 |\n\
+             | 1:25 | c | c | 10 |\n\n\
+             ## a\n\n\
+             ### 0:00 a (5 min)\n\n\
+             Notes for *A*.\n\n\
+             ### 0:05 b (70 min)\n\n\
+             ## c\n\n\
+             ### 1:25 c (10 min)\n\n"
+        );
+        assert!(run_sheet.to_html().contains("<p>Notes for <em>A</em>.</p>"));
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Books and courses shared by the tests of each module.
+
+use crate::config::Config;
+use crate::course::Courses;
+use mdbook::book::{Book, BookItem, Chapter};
+
+/// Create a chapter with the given content, at `{name}.md`, with the given
+/// sub-chapters.
+pub fn chapter(name: &str, content: &str, sub_items: Vec<Chapter>) -> Chapter {
+    let mut chapter =
+        Chapter::new(name, content.into(), format!("{name}.md"), Vec::new());
+    chapter.sub_items = sub_items.into_iter().map(BookItem::Chapter).collect();
+    chapter
+}
+
+/// Create a book of the given top-level chapters.
+pub fn book(chapters: impl IntoIterator<Item = Chapter>) -> Book {
+    let mut book = Book::new();
+    for chapter in chapters {
+        book.push_item(chapter);
+    }
+    book
+}
+
+/// Extract the course structure from the book with the default configuration.
+pub fn extract(book: Book) -> (Courses, Book) {
+    Courses::extract_structure(book, &Config::default()).unwrap()
+}
+
+/// Extract the courses in a book of the given top-level chapters.
+pub fn courses(chapters: impl IntoIterator<Item = Chapter>) -> Courses {
+    extract(book(chapters)).0
+}
@@ -12,26 +12,227 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::course::Slide;
+use crate::config::TimingConfig;
+use crate::course::{Courses, Slide};
+use crate::markdown::{duration, round_minutes, speaker_notes};
 use mdbook::book::Chapter;
 
+/// The timing of one appearance of a slide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Timing {
+    minutes: u64,
+    /// Minutes of the segment elapsed before this slide.
+    elapsed: u64,
+    segment_minutes: u64,
+}
+
 /// Insert timing information for this slide into the speaker notes.
-pub fn insert_timing_info(slide: &Slide, chapter: &mut Chapter) {
-    if slide.minutes > 0
-        && !slide.is_sub_chapter(chapter)
-        && chapter.content.contains("<details>")
-    {
-        // Include the minutes in the speaker notes.
-        let minutes = slide.minutes;
-        let plural = if slide.minutes == 1 { "minute" } else { "minutes" };
-        let mut subslides = "";
-        if slide.source_paths.len() > 1 {
-            subslides = "and its sub-slides ";
+///
+/// The speaker notes are the last top-level `<details>` block in the chapter.
+/// The timing is inserted as a separate paragraph at the beginning of the
+/// notes. If the chapter has no speaker notes, a notes block is only added if
+/// configured.
+///
+/// If the chapter is used in several courses with different timings, the
+/// timing for each course is given, since all appearances of the chapter are
+/// rendered to the same page.
+pub fn insert_timing_info(
+    courses: &Courses,
+    slide: &Slide,
+    config: &TimingConfig,
+    chapter: &mut Chapter,
+) {
+    if slide.minutes == 0 || slide.is_sub_chapter(chapter) {
+        return;
+    }
+    let Some(source_path) = &chapter.source_path else {
+        return;
+    };
+
+    let mut timings: Vec<(&str, Timing)> = Vec::new();
+    for (course, _, segment, slide) in courses.find_slides(source_path) {
+        if slide.source_path() == Some(source_path)
+            && !timings.iter().any(|(name, _)| *name == course.name)
+        {
+            let timing = Timing {
+                minutes: slide.minutes(),
+                elapsed: segment.minutes_before(slide),
+                segment_minutes: segment.minutes(),
+            };
+            timings.push((&course.name, timing));
+        }
+    }
+    let sub_slides = slide.source_paths.len() > 1;
+    let message = |course: Option<&str>, timing: &Timing| {
+        let this = match course {
+            Some(course) => format!("In the {course} course, this"),
+            None => "This".into(),
+        };
+        let slide = if sub_slides { "slide and its sub-slides" } else { "slide" };
+        // Round like the outlines, so that the segment has the same duration.
+        format!(
+            "{this} {slide} should take about {}, starting at minute {} of the \
+             {}-minute segment.",
+            duration(timing.minutes, config.rounding),
+            round_minutes(timing.elapsed, config.rounding),
+            round_minutes(timing.segment_minutes, config.rounding)
+        )
+    };
+    let paragraphs: Vec<String> = match timings.as_slice() {
+        [] => return,
+        [(_, first), rest @ ..] if rest.iter().all(|(_, t)| t == first) => {
+            vec![message(None, first)]
         }
-        let timing_message =
-            format!("This slide {subslides}should take about {minutes} {plural}. ");
-        chapter.content = chapter
-            .content
-            .replace("<details>", &format!("<details>\n{timing_message}"));
+        _ => timings
+            .iter()
+            .filter(|(_, t)| t.minutes > 0)
+            .map(|(course, t)| message(Some(course), t))
+            .collect(),
+    };
+    let timing_message = paragraphs.join("\n\n");
+
+    match speaker_notes(&chapter.content) {
+        Some(notes) => {
+            // Separate the message from the notes by exactly one blank line.
+            let rest = &chapter.content[notes.start..];
+            let newlines = rest.len() - rest.trim_start_matches('\n').len();
+            let padding = "\n".repeat(2usize.saturating_sub(newlines));
+            chapter
+                .content
+                .insert_str(notes.start, &format!("\n\n{timing_message}{padding}"));
+        }
+        None if config.create_notes => {
+            chapter.content.push_str(&format!(
+                "\n\n<details>\n\n{timing_message}\n\n</details>\n"
+            ));
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{chapter, extract};
+    use mdbook::book::{Book, BookItem};
+
+    /// Insert timing info into every chapter of the book, returning the
+    /// content of each chapter.
+    fn insert(book: Book, config: &TimingConfig) -> Vec<String> {
+        let (courses, mut book) = extract(book);
+        book.for_each_mut(|item| {
+            if let BookItem::Chapter(chapter) = item
+                && let Some((_, _, _, slide)) = courses.find_slide(chapter)
+            {
+                insert_timing_info(&courses, slide, config, chapter);
+            }
+        });
+        book.iter()
+            .filter_map(|item| match item {
+                BookItem::Chapter(chapter) => Some(chapter.content.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn test_book(notes: &str) -> Book {
+        let mut book = Book::new();
+        book.push_item(chapter(
+            "a",
+            "---\ncourse: C\nsession: S\nminutes: 5\n---\nA",
+            vec![
+                chapter(
+                    "b",
+                    &format!("---\nminutes: 10\n---\nB\n{notes}"),
+                    Vec::new(),
+                ),
+                chapter("c", "---\nminutes: 1\n---\nC", Vec::new()),
+            ],
+        ));
+        book
+    }
+
+    #[test]
+    fn notes_formats() {
+        let config = TimingConfig::default();
+        let message = "This slide should take about 10 minutes, starting at minute 5 \
+                       of the 20-minute segment.";
+        assert_eq!(
+            insert(
+                test_book("<details open class=\"x\">\nNotes\n</details>"),
+                &config
+            )[1],
+            format!(
+                "B\n<details open class=\"x\">\n\n{message}\n\nNotes\n</details>"
+            )
+        );
+        assert_eq!(
+            insert(
+                test_book(
+                    "<details>\n\nFirst\n\n</details>\n\n<DETAILS>Notes</DETAILS>"
+                ),
+                &config
+            )[1],
+            format!(
+                "B\n<details>\n\nFirst\n\n</details>\n\n<DETAILS>\n\n{message}\n\n\
+                 Notes</DETAILS>"
+            )
+        );
+        assert_eq!(
+            insert(test_book("```html\n<details>\n```\n"), &config)[1],
+            "B\n```html\n<details>\n```"
+        );
+    }
+
+    #[test]
+    fn create_notes() {
+        let config = TimingConfig { create_notes: true, ..Default::default() };
+        assert_eq!(
+            insert(test_book(""), &config),
+            vec![
+                "A\n\n<details>\n\nThis slide should take about 5 minutes, starting at \
+                 minute 0 of the 20-minute segment.\n\n</details>\n",
+                "B\n\n<details>\n\nThis slide should take about 10 minutes, starting \
+                 at minute 5 of the 20-minute segment.\n\n</details>\n",
+                "C\n\n<details>\n\nThis slide should take about 1 minute, starting at \
+                 minute 15 of the 20-minute segment.\n\n</details>\n",
+            ]
+        );
+        assert_eq!(insert(test_book(""), &TimingConfig::default())[1], "B");
+        // Without rounding, the times are exact.
+        let config = TimingConfig { create_notes: true, rounding: 1, ..config };
+        assert!(insert(test_book(""), &config)[2].contains(
+            "This slide should take about 1 minute, starting at minute 15 of the \
+             16-minute segment."
+        ));
+    }
+
+    #[test]
+    fn per_course() {
+        let mut book = Book::new();
+        let shared = || {
+            chapter(
+                "s",
+                "---\nminutes: { A: 5, B: 10 }\n---\n<details>\n</details>",
+                Vec::new(),
+            )
+        };
+        book.push_item(chapter(
+            "a",
+            "---\ncourse: A\nsession: S\nminutes: 1\n---\n",
+            vec![shared()],
+        ));
+        book.push_item(chapter(
+            "b",
+            "---\ncourse: B\nsession: S\nminutes: 1\n---\n",
+            vec![shared()],
+        ));
+        assert_eq!(
+            insert(book, &TimingConfig::default())[1],
+            "<details>\n\nIn the A course, this slide should take about 5 minutes, \
+             starting at minute 1 of the 10-minute segment.\n\n\
+             In the B course, this slide should take about 10 minutes, starting at \
+             minute 1 of the 15-minute segment.\n\n</details>"
+        );
     }
 }